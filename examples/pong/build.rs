@@ -18,24 +18,23 @@ fn main() {
     copy_dir_content_recursive(source_assets_dir.as_path(), &destination_assets_dir);
 }
 
-fn copy_dir_content_recursive(dir: &Path, destination_dir: &PathBuf) {
+fn copy_dir_content_recursive(dir: &Path, destination_dir: &Path) {
     if dir.is_dir() {
-        if !destination_dir.as_path().exists() {
-            fs::create_dir(destination_dir.as_path());
+        if !destination_dir.exists() {
+            fs::create_dir(destination_dir).expect("Can't create Resource dir");
         }
 
         for entry in fs::read_dir(dir).unwrap() {
             let entry = entry.unwrap();
             let path = entry.path();
             if path.is_dir() {
-                let mut new_destination = destination_dir.clone();
+                let mut new_destination = destination_dir.to_path_buf();
                 new_destination.push(entry.file_name());
                 copy_dir_content_recursive(&path, &new_destination);
             } else {
-                let mut new_file_path = destination_dir.clone();
+                let mut new_file_path = destination_dir.to_path_buf();
                 new_file_path.push(entry.file_name());
-                std::fs::copy(&path, new_file_path.as_path())
-                    .expect("Can't copy from Resource dir");
+                fs::copy(&path, new_file_path.as_path()).expect("Can't copy from Resource dir");
             }
         }
     }
@@ -1,16 +1,214 @@
-use jelly_engine::engine::Config;
+use jelly_engine::engine::prelude::*;
+use jelly_engine::error::Result;
+use jelly_engine::graphics::prelude::{Color, PostEffect};
+use jelly_engine::input::prelude::InputMap;
+use jelly_engine::log::{self, LevelFilter};
+use jelly_engine::logging::Logger;
+use jelly_engine::text::prelude::Font;
 
 extern crate jelly_engine;
 
-fn main() -> Result<(), String> {
-    jelly_engine::engine::start(Config {
-        title: String::from("Pong"),
-        fullscreen: false,
-        virtual_width: 128,
-        virtual_height: 128,
-        screen_width: 800,
-        screen_height: 800,
-    })?;
+const WIDTH: f32 = 128.0;
+const HEIGHT: f32 = 128.0;
+
+const PADDLE_WIDTH: f32 = 2.0;
+const PADDLE_HEIGHT: f32 = 16.0;
+const PADDLE_SPEED: f32 = 80.0;
+const PADDLE_MARGIN: f32 = 4.0;
+
+const BALL_SIZE: f32 = 2.0;
+const BALL_SPEED: f32 = 60.0;
+
+const TRAIL_LENGTH: usize = 12;
+
+const DASH_LENGTH: f32 = 4.0;
+
+#[derive(Default)]
+struct Paddle {
+    y: f32,
+}
+
+impl Paddle {
+    fn update(&mut self, direction: f32, dt: f32) {
+        self.y += direction * PADDLE_SPEED * dt;
+        self.y = self.y.clamp(0.0, HEIGHT - PADDLE_HEIGHT);
+    }
+
+    fn hits(&self, x: f32, ball_x: f32, ball_y: f32) -> bool {
+        ball_x < x + PADDLE_WIDTH
+            && ball_x + BALL_SIZE > x
+            && ball_y < self.y + PADDLE_HEIGHT
+            && ball_y + BALL_SIZE > self.y
+    }
+}
+
+#[derive(Default)]
+struct Pong {
+    bindings: InputMap,
+
+    left: Paddle,
+    right: Paddle,
+
+    ball: (f32, f32),
+    previous_ball: (f32, f32),
+    velocity: (f32, f32),
+
+    // Previous ball positions, oldest first
+    trail: Vec<(f32, f32)>,
+
+    score: (u32, u32),
+
+    font: Option<Font>,
+    ball_color: Color,
+}
+
+impl Pong {
+    fn serve(&mut self, direction: f32) {
+        self.ball = ((WIDTH - BALL_SIZE) / 2.0, (HEIGHT - BALL_SIZE) / 2.0);
+        self.previous_ball = self.ball;
+        self.velocity = (BALL_SPEED * direction, BALL_SPEED / 2.0);
+        self.trail.clear();
+    }
+}
+
+impl Game for Pong {
+    fn init(&mut self, ctx: &mut Context) -> Result<()> {
+        let mut bindings_path = std::env::current_exe()?;
+        bindings_path.pop();
+        bindings_path.push("assets/bindings.ron");
+        self.bindings = InputMap::load(bindings_path)?;
+
+        self.left.y = (HEIGHT - PADDLE_HEIGHT) / 2.0;
+        self.right.y = (HEIGHT - PADDLE_HEIGHT) / 2.0;
+        self.serve(1.0);
+
+        if ctx.is_headless() {
+            return Ok(());
+        }
+
+        ctx.clear_color = Color::from_palette("dark_blue")?;
+
+        self.ball_color = Color::from_palette("yellow")?;
+
+        self.font = Some(Font::debug());
+
+        ctx.post_process.push(PostEffect::bloom());
+        ctx.post_process.push(PostEffect::vignette());
+
+        Ok(())
+    }
+
+    fn update(&mut self, ctx: &mut Context, dt: f32) {
+        let input = &ctx.input;
+
+        for (name, paddle) in [("left", &mut self.left), ("right", &mut self.right)] {
+            if let Some(profile) = self.bindings.profile(name) {
+                if profile.just_pressed(input, "quit") {
+                    ctx.quit();
+                    return;
+                }
+
+                paddle.update(profile.axis(input, "paddle"), dt);
+            }
+        }
+
+        self.previous_ball = self.ball;
+        self.trail.push(self.ball);
+        if self.trail.len() > TRAIL_LENGTH {
+            self.trail.remove(0);
+        }
+
+        self.ball.0 += self.velocity.0 * dt;
+        self.ball.1 += self.velocity.1 * dt;
+
+        if self.ball.1 <= 0.0 || self.ball.1 + BALL_SIZE >= HEIGHT {
+            self.velocity.1 = -self.velocity.1;
+            self.ball.1 = self.ball.1.clamp(0.0, HEIGHT - BALL_SIZE);
+        }
+
+        let left_x = PADDLE_MARGIN;
+        let right_x = WIDTH - PADDLE_MARGIN - PADDLE_WIDTH;
+
+        if self.velocity.0 < 0.0 && self.left.hits(left_x, self.ball.0, self.ball.1) {
+            self.velocity.0 = -self.velocity.0;
+            ctx.camera.add_trauma(0.3);
+        }
+        if self.velocity.0 > 0.0 && self.right.hits(right_x, self.ball.0, self.ball.1) {
+            self.velocity.0 = -self.velocity.0;
+            ctx.camera.add_trauma(0.3);
+        }
+
+        if self.ball.0 + BALL_SIZE < 0.0 {
+            self.score.1 += 1;
+            log::info!("Score: {} - {}", self.score.0, self.score.1);
+            self.serve(1.0);
+        } else if self.ball.0 > WIDTH {
+            self.score.0 += 1;
+            log::info!("Score: {} - {}", self.score.0, self.score.1);
+            self.serve(-1.0);
+        }
+    }
+
+    fn render(&mut self, ctx: &mut Context) {
+        let alpha = ctx.time.alpha();
+        let mut draw = ctx.draw();
+
+        // Shapes share the white texture, so the whole field takes a single draw call
+        draw.set_color(Color {
+            a: 0.3,
+            ..Color::white()
+        });
+        let mut y = DASH_LENGTH / 2.0;
+        while y < HEIGHT {
+            draw.line(WIDTH / 2.0, y, WIDTH / 2.0, y + DASH_LENGTH);
+            y += DASH_LENGTH * 2.0;
+        }
+
+        draw.set_color(Color::white());
+        draw.fill_rect(PADDLE_MARGIN, self.left.y, PADDLE_WIDTH, PADDLE_HEIGHT);
+        draw.fill_rect(
+            WIDTH - PADDLE_MARGIN - PADDLE_WIDTH,
+            self.right.y,
+            PADDLE_WIDTH,
+            PADDLE_HEIGHT,
+        );
+
+        for (i, (x, y)) in self.trail.iter().enumerate() {
+            let mut tint = self.ball_color;
+            tint.a = 0.5 * (i + 1) as f32 / (TRAIL_LENGTH + 1) as f32;
+            draw.set_color(tint);
+            draw.fill_rect(*x, *y, BALL_SIZE, BALL_SIZE);
+        }
+
+        let x = self.previous_ball.0 + (self.ball.0 - self.previous_ball.0) * alpha;
+        let y = self.previous_ball.1 + (self.ball.1 - self.previous_ball.1) * alpha;
+        draw.set_color(self.ball_color);
+        draw.fill_rect(x, y, BALL_SIZE, BALL_SIZE);
+
+        if let Some(font) = &self.font {
+            let score = format!("{}  {}", self.score.0, self.score.1);
+            let width = font.measure(&score).x;
+            draw.set_color(Color::white());
+            draw.text(font, &score, ((WIDTH - width) / 2.0).round(), 4.0);
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    Logger::new(LevelFilter::Info)
+        .with_target("jelly_engine::gl", LevelFilter::Warn)
+        .init()?;
+
+    jelly_engine::engine::start(
+        Config {
+            title: String::from("Pong"),
+            virtual_width: WIDTH as u32,
+            virtual_height: HEIGHT as u32,
+            canvas: CanvasMode::IntegerScale,
+            ..Config::default()
+        },
+        &mut Pong::default(),
+    )?;
 
     Ok(())
 }
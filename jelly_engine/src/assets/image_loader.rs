@@ -1,5 +1,9 @@
+use std::path::Path;
+
 use image::{DynamicImage, GenericImageView};
 
+use crate::error::{JellyError, Result};
+
 extern crate image;
 
 #[derive(Debug, Clone)]
@@ -10,30 +14,38 @@ pub struct ImageAsset {
     pub data: Vec<u8>,
 }
 
-pub fn load(image_name: &str) -> ImageAsset {
-    match std::env::current_exe() {
-        Ok(mut absolute_path) => {
-            absolute_path.pop();
-            absolute_path.push("assets/images/");
-            absolute_path.push(image_name);
-
-            match image::open(absolute_path) {
-                Ok(img) => {
-                    let (width, height) = img.dimensions();
-                    let img = match img {
-                        DynamicImage::ImageRgba8(img) => img,
-                        img => img.to_rgba8(),
-                    };
-
-                    ImageAsset {
-                        width,
-                        height,
-                        data: img.into_raw(),
-                    }
-                }
-                Err(e) => panic!("Could not load image {}: {}", image_name, e),
-            }
+pub fn load(image_name: &str) -> Result<ImageAsset> {
+    let mut absolute_path = std::env::current_exe()?;
+    absolute_path.pop();
+    absolute_path.push("assets/images/");
+    absolute_path.push(image_name);
+
+    load_path(&absolute_path)
+}
+
+/// Load an image from anywhere on disk
+pub fn load_path(path: &Path) -> Result<ImageAsset> {
+    if !path.exists() {
+        return Err(JellyError::AssetNotFound(path.to_path_buf()));
+    }
+
+    match image::open(path) {
+        Ok(img) => {
+            let (width, height) = img.dimensions();
+            let img = match img {
+                DynamicImage::ImageRgba8(img) => img,
+                img => img.to_rgba8(),
+            };
+
+            Ok(ImageAsset {
+                width,
+                height,
+                data: img.into_raw(),
+            })
         }
-        Err(e) => panic!("Current exe path error: {}", e),
+        Err(source) => Err(JellyError::ImageDecode {
+            name: path.display().to_string(),
+            source,
+        }),
     }
 }
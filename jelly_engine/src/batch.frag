@@ -0,0 +1,15 @@
+#version 410
+
+precision mediump float;
+
+uniform sampler2D u_diffuse;
+
+in vec2 v_tex_coord;
+in vec4 v_color;
+
+out vec4 fragColor;
+
+void main()
+{
+    fragColor = v_color * texture(u_diffuse, v_tex_coord);
+}
@@ -0,0 +1,17 @@
+#version 410
+
+in vec3 a_position;
+in vec2 a_tex_coord;
+in vec4 a_color;
+
+uniform mat4 u_projection;
+
+out vec2 v_tex_coord;
+out vec4 v_color;
+
+void main()
+{
+    gl_Position = u_projection * vec4(a_position, 1.0);
+    v_tex_coord = a_tex_coord;
+    v_color = a_color;
+}
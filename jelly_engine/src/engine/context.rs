@@ -0,0 +1,173 @@
+use sdl2::video::Window;
+
+use super::time::Time;
+use crate::gl_utilities::prelude::ShaderManager;
+use crate::graphics::prelude::{
+    Camera2D, Color, Draw, PostProcess, ShapeRenderer, SpriteBatch, TextureManager,
+};
+use crate::input::prelude::{Gamepads, Input};
+
+/// Engine state handed to every `Game` callback.
+pub struct Context {
+    /// `None` when running headless
+    pub window: Option<Window>,
+    pub shader_manager: ShaderManager,
+    pub textures: TextureManager,
+    /// Batched quads, drawn at the end of `Game::render`
+    pub batch: SpriteBatch,
+    /// Colored shapes, queued in `batch`
+    pub shapes: ShapeRenderer,
+    /// Main view of the world, its view-projection is handed to the shaders every frame
+    pub camera: Camera2D,
+    /// Extra cameras by name, see `add_camera`
+    cameras: Vec<(String, Camera2D)>,
+    // Name of the camera `Game::render` draws for, `None` for the main one
+    rendering_camera: Option<String>,
+    /// Effects applied to the canvas, when the engine renders to one
+    pub post_process: PostProcess,
+    pub time: Time,
+    pub input: Input,
+    /// `None` when running headless
+    pub gamepads: Option<Gamepads>,
+
+    /// Color used to clear the virtual screen area every frame
+    pub clear_color: Color,
+
+    pub virtual_width: u32,
+    pub virtual_height: u32,
+
+    should_quit: bool,
+}
+
+impl Context {
+    pub fn new(
+        window: Option<Window>,
+        shader_manager: ShaderManager,
+        time: Time,
+        virtual_width: u32,
+        virtual_height: u32,
+    ) -> Context {
+        let (batch, shapes) = if window.is_some() {
+            (SpriteBatch::new(), ShapeRenderer::new())
+        } else {
+            (SpriteBatch::headless(), ShapeRenderer::headless())
+        };
+
+        Context {
+            window,
+            shader_manager,
+            textures: TextureManager::new(),
+            batch,
+            shapes,
+            camera: Camera2D::new(virtual_width, virtual_height),
+            cameras: Vec::new(),
+            rendering_camera: None,
+            post_process: PostProcess::new(),
+            time,
+            input: Input::new(),
+            gamepads: None,
+            clear_color: Color::white(),
+            virtual_width,
+            virtual_height,
+            should_quit: false,
+        }
+    }
+
+    /// Immediate drawing into `batch`, with its own color and transform stack
+    pub fn draw(&mut self) -> Draw<'_> {
+        Draw::new(&mut self.batch, &self.shapes, self.time.elapsed())
+    }
+
+    /// Ask the engine to leave the main loop at the end of the current frame
+    pub fn quit(&mut self) {
+        self.should_quit = true;
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+
+    /// True when there is no window nor GL context, so nothing can be drawn
+    pub fn is_headless(&self) -> bool {
+        self.window.is_none()
+    }
+
+    /// Render the frame through one more camera, e.g. for split screens or a
+    /// minimap. `Game::render` is called once per enabled camera, in their order
+    pub fn add_camera(&mut self, name: &str, camera: Camera2D) {
+        match self.find_camera_mut(name) {
+            Some(current) => *current = camera,
+            None => self.cameras.push((String::from(name), camera)),
+        }
+    }
+
+    pub fn remove_camera(&mut self, name: &str) -> Option<Camera2D> {
+        let index = self.cameras.iter().position(|(camera, _)| camera == name)?;
+
+        Some(self.cameras.remove(index).1)
+    }
+
+    pub fn find_camera(&self, name: &str) -> Option<&Camera2D> {
+        self.cameras
+            .iter()
+            .find(|(camera, _)| camera == name)
+            .map(|(_, camera)| camera)
+    }
+
+    pub fn find_camera_mut(&mut self, name: &str) -> Option<&mut Camera2D> {
+        self.cameras
+            .iter_mut()
+            .find(|(camera, _)| camera == name)
+            .map(|(_, camera)| camera)
+    }
+
+    /// Camera the current `Game::render` call draws for, the main one if it
+    /// was removed during the call
+    pub fn rendering_camera(&self) -> &Camera2D {
+        self.camera_named(self.rendering_camera_name())
+            .unwrap_or(&self.camera)
+    }
+
+    /// Name of the camera the current `Game::render` call draws for, `None` for the main one
+    pub fn rendering_camera_name(&self) -> Option<&str> {
+        self.rendering_camera.as_deref()
+    }
+
+    /// Enabled cameras in drawing order, `None` standing for the main camera
+    pub(super) fn render_order(&self) -> Vec<Option<String>> {
+        let mut order: Vec<(Option<String>, i32)> = std::iter::once((None, &self.camera))
+            .chain(
+                self.cameras
+                    .iter()
+                    .map(|(name, camera)| (Some(name.clone()), camera)),
+            )
+            .filter(|(_, camera)| camera.enabled)
+            .map(|(name, camera)| (name, camera.order))
+            .collect();
+
+        // Stable, so the main camera and earlier cameras go first on ties
+        order.sort_by_key(|(_, order)| *order);
+
+        order.into_iter().map(|(name, _)| name).collect()
+    }
+
+    pub(super) fn set_rendering_camera(&mut self, camera: Option<String>) {
+        self.rendering_camera = camera;
+    }
+
+    /// `None` stands for the main camera, a removed camera gives `None`
+    pub(super) fn camera_named(&self, camera: Option<&str>) -> Option<&Camera2D> {
+        match camera {
+            Some(name) => self.find_camera(name),
+            None => Some(&self.camera),
+        }
+    }
+
+    /// Advance every camera's shake
+    pub(super) fn update_cameras(&mut self, dt: f32) {
+        self.camera.update(dt);
+        for (_, camera) in &mut self.cameras {
+            camera.update(dt);
+        }
+    }
+}
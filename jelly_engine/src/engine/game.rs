@@ -0,0 +1,23 @@
+use sdl2::event::Event;
+
+use super::context::Context;
+use crate::error::Result;
+
+/// Callbacks driven by `engine::start`.
+///
+/// `init` is called once the window and GL context are ready, then every frame
+/// the engine forwards SDL events to `on_event`, runs as many fixed `update`
+/// steps as the elapsed time requires and calls `render` once per enabled
+/// camera, `Context::rendering_camera` telling which one it draws for.
+/// `ctx.time.alpha()` tells `render` how far it is between two updates.
+pub trait Game {
+    /// Errors are returned as is by `engine::start`
+    fn init(&mut self, ctx: &mut Context) -> Result<()>;
+
+    /// `dt` is always the fixed update delta, in seconds
+    fn update(&mut self, ctx: &mut Context, dt: f32);
+
+    fn render(&mut self, ctx: &mut Context);
+
+    fn on_event(&mut self, _ctx: &mut Context, _event: &Event) {}
+}
@@ -0,0 +1,103 @@
+use super::{render_cameras, simulate, Config, Context, Game, ManualClock, Time};
+use crate::error::Result;
+use crate::gl_utilities::prelude::ShaderManager;
+use crate::graphics::prelude::Viewport;
+
+/// Run the game loop without SDL nor GL.
+///
+/// Time is driven by a `ManualClock` advanced by exactly one fixed update per
+/// frame, so a run is deterministic and doesn't wait on the wall clock.
+/// `Game::render` is still called every frame with `ctx.window` set to `None`,
+/// games are expected to check `ctx.is_headless()` before touching GL resources.
+/// `ctx.batch` still counts quads and draw calls but sends nothing to GL, and
+/// every enabled camera still gets its `Game::render` call.
+/// Input can be fed through `ctx.input`, mouse positions map 1:1 to virtual coordinates.
+pub fn run<G: Game>(config: Config, game: &mut G) -> Result<()> {
+    let clock = ManualClock::new();
+    let time = Time::with_clock(config.update_rate, Box::new(clock.clone()));
+    let frame_time = time.fixed_duration();
+
+    let mut ctx = Context::new(
+        None,
+        ShaderManager::headless(),
+        time,
+        config.virtual_width,
+        config.virtual_height,
+    );
+
+    let virtual_size = (config.virtual_width, config.virtual_height);
+    ctx.input.set_viewport(
+        Viewport::new(0, 0, virtual_size.0 as i32, virtual_size.1 as i32),
+        virtual_size,
+    );
+
+    game.init(&mut ctx)?;
+
+    ctx.time.reset();
+    loop {
+        clock.advance(frame_time);
+
+        if !simulate(&mut ctx, game, config.max_frames) {
+            break;
+        }
+
+        render_cameras(&mut ctx, game, None, Viewport::default())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::prelude::Camera2D;
+
+    // Removes the cameras drawn after the current one during render
+    #[derive(Default)]
+    struct Remover {
+        rendered: Vec<Option<String>>,
+    }
+
+    impl Game for Remover {
+        fn init(&mut self, ctx: &mut Context) -> Result<()> {
+            for (name, order) in [("left", 1), ("right", 2)] {
+                let mut camera = Camera2D::new(ctx.virtual_width, ctx.virtual_height);
+                camera.order = order;
+                ctx.add_camera(name, camera);
+            }
+
+            Ok(())
+        }
+
+        fn update(&mut self, _ctx: &mut Context, _dt: f32) {}
+
+        fn render(&mut self, ctx: &mut Context) {
+            let name = ctx.rendering_camera_name().map(String::from);
+            match name.as_deref() {
+                None => {
+                    ctx.remove_camera("right");
+                }
+                Some("left") => {
+                    ctx.remove_camera("left");
+                    // Falls back to the main camera
+                    assert_eq!(ctx.rendering_camera().order, 0);
+                }
+                _ => {}
+            }
+
+            self.rendered.push(name);
+        }
+    }
+
+    #[test]
+    fn cameras_removed_during_render_are_skipped() {
+        let config = Config {
+            max_frames: Some(2),
+            ..Config::default()
+        };
+        let mut game = Remover::default();
+        run(config, &mut game).unwrap();
+
+        assert_eq!(game.rendered, [None, Some(String::from("left")), None]);
+    }
+}
@@ -0,0 +1,474 @@
+extern crate gl;
+extern crate sdl2;
+
+mod context;
+mod game;
+mod headless;
+mod time;
+
+use std::time::{Duration, Instant};
+
+use sdl2::{
+    event::{Event, WindowEvent},
+    video::{DisplayMode, FullscreenType, GLProfile, SwapInterval},
+    VideoSubsystem,
+};
+
+use crate::error::{JellyError, Result};
+use crate::gl_utilities::{debug, prelude::ShaderManager};
+use crate::graphics::prelude::{Color, PostProcess, RenderTarget, Viewport};
+use crate::input::prelude::Gamepads;
+use crate::math::prelude::Matrix4x4;
+
+pub use context::Context;
+pub use game::Game;
+pub use time::{Clock, ManualClock, SystemClock, Time};
+
+pub mod prelude {
+    pub use crate::engine::{start, CanvasMode, Config, Context, Game, Time};
+}
+
+/// How the virtual resolution reaches the window
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum CanvasMode {
+    /// Draw straight to the window, the virtual resolution only sets the projection
+    #[default]
+    Off,
+    /// Draw into a virtual size canvas, stretched to the largest size fitting the window
+    Letterbox,
+    /// Draw into a virtual size canvas, scaled by a whole factor to keep pixels square
+    IntegerScale,
+}
+
+pub struct Config {
+    pub title: String,
+    pub fullscreen: bool,
+    pub virtual_width: u32,
+    pub virtual_height: u32,
+    pub screen_width: u32,
+    pub screen_height: u32,
+    /// Fixed simulation ticks per second
+    pub update_rate: u32,
+    /// Cap on rendered frames per second, `None` renders as fast as possible
+    pub frame_limit: Option<u32>,
+    pub vsync: bool,
+    /// Run without window, GL context or rendering, see `headless`
+    pub headless: bool,
+    /// Stop after this many frames, `None` runs until the game quits
+    pub max_frames: Option<u64>,
+    /// Also log GL debug messages of notification severity
+    pub gl_debug_notifications: bool,
+    /// Extra gamepad mappings in the gamecontrollerdb.txt format
+    pub gamepad_mappings: Option<String>,
+    /// Render at the virtual resolution and upscale, for a crisp pixel-art look
+    pub canvas: CanvasMode,
+    /// Source of time of the loop, `None` for a `SystemClock`. Headless runs
+    /// ignore it and step their own `ManualClock`
+    pub clock: Option<Box<dyn Clock>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            title: String::from("JellyEngine"),
+            fullscreen: false,
+            virtual_width: 128,
+            virtual_height: 128,
+            screen_width: 800,
+            screen_height: 800,
+            update_rate: 60,
+            frame_limit: None,
+            vsync: true,
+            headless: false,
+            max_frames: None,
+            gl_debug_notifications: false,
+            gamepad_mappings: None,
+            canvas: CanvasMode::default(),
+            clock: None,
+        }
+    }
+}
+
+pub fn start<G: Game>(mut config: Config, game: &mut G) -> Result<()> {
+    log::info!("Hello, JellyEngine!");
+
+    if config.headless {
+        return headless::run(config, game);
+    }
+
+    // Init window
+    let sdl_context = sdl2::init().map_err(JellyError::Sdl)?;
+    let video_subsystem = sdl_context.video().map_err(JellyError::Sdl)?;
+
+    let gl_attr = video_subsystem.gl_attr();
+    gl_attr.set_context_profile(GLProfile::Core);
+
+    if cfg!(target_os = "macos") {
+        gl_attr.set_context_version(4, 1);
+    } else {
+        gl_attr.set_context_version(4, 6);
+    }
+    gl_attr.set_double_buffer(true);
+
+    let mut window = video_subsystem
+        .window(
+            config.title.as_ref(),
+            config.screen_width,
+            config.screen_height,
+        )
+        .opengl()
+        .allow_highdpi()
+        .build()
+        .map_err(|e| JellyError::Sdl(e.to_string()))?;
+
+    if config.fullscreen {
+        let display_mode = get_display_mode(&video_subsystem, &config)?;
+        window
+            .set_display_mode(display_mode)
+            .map_err(JellyError::Sdl)?;
+        window
+            .set_fullscreen(FullscreenType::True)
+            .map_err(JellyError::Sdl)?;
+    }
+
+    let _ctx = window.gl_create_context().map_err(JellyError::Sdl)?;
+    gl::load_with(|s| video_subsystem.gl_get_proc_address(s) as *const _);
+
+    let swap_interval = if config.vsync {
+        SwapInterval::VSync
+    } else {
+        SwapInterval::Immediate
+    };
+    video_subsystem
+        .gl_set_swap_interval(swap_interval)
+        .map_err(JellyError::Sdl)?;
+
+    debug::enable_debug_output(config.gl_debug_notifications);
+
+    unsafe {
+        gl::Enable(gl::BLEND);
+        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+    }
+
+    log::info!(
+        "Pixel format of the window's GL context: {:?}",
+        window.window_pixel_format()
+    );
+    log::info!(
+        "OpenGL Profile: {:?} - OpenGL version: {:?}",
+        gl_attr.context_profile(),
+        gl_attr.context_version(),
+    );
+
+    let mut shader_manager = ShaderManager::init()?;
+    shader_manager.register(
+        "basic",
+        include_str!("../basic.vert"),
+        include_str!("../basic.frag"),
+    )?;
+    shader_manager.register(
+        "batch",
+        include_str!("../batch.vert"),
+        include_str!("../batch.frag"),
+    )?;
+    PostProcess::register_builtins(&mut shader_manager)?;
+
+    let mut ctx = Context::new(
+        Some(window),
+        shader_manager,
+        match config.clock.take() {
+            Some(clock) => Time::with_clock(config.update_rate, clock),
+            None => Time::new(config.update_rate),
+        },
+        config.virtual_width,
+        config.virtual_height,
+    );
+
+    ctx.batch.set_shader(ctx.shader_manager.get("batch")?)?;
+
+    let canvas = match config.canvas {
+        CanvasMode::Off => None,
+        _ => Some(RenderTarget::new(
+            config.virtual_width,
+            config.virtual_height,
+            false,
+        )?),
+    };
+
+    let mut screen_viewport = resize(&mut ctx, config.canvas);
+
+    let gamepads = Gamepads::new(
+        sdl_context.game_controller().map_err(JellyError::Sdl)?,
+        sdl_context.joystick().map_err(JellyError::Sdl)?,
+    );
+    if let Some(path) = &config.gamepad_mappings {
+        let added = gamepads.load_mappings(path)?;
+        log::info!("Loaded {} gamepad mappings from {}", added, path);
+    }
+    ctx.gamepads = Some(gamepads);
+
+    game.init(&mut ctx)?;
+
+    let frame_duration = config
+        .frame_limit
+        .map(|limit| Duration::from_secs_f64(1.0 / limit.max(1) as f64));
+
+    let mut event_pump = sdl_context.event_pump().map_err(JellyError::Sdl)?;
+    ctx.time.reset();
+    'main_loop: loop {
+        let frame_start = Instant::now();
+
+        for event in event_pump.poll_iter() {
+            match event {
+                Event::Quit { .. } => break 'main_loop,
+                Event::Window {
+                    win_event: WindowEvent::SizeChanged(..),
+                    ..
+                } => screen_viewport = resize(&mut ctx, config.canvas),
+                Event::ControllerDeviceAdded { .. } | Event::ControllerDeviceRemoved { .. } => {
+                    if let Some(gamepads) = &mut ctx.gamepads {
+                        gamepads.handle_event(&event, &mut ctx.input);
+                    }
+                }
+                _ => ctx.input.handle_event(&event),
+            }
+
+            game.on_event(&mut ctx, &event);
+        }
+
+        if !simulate(&mut ctx, game, config.max_frames) {
+            break 'main_loop;
+        }
+
+        let area = match &canvas {
+            Some(canvas) => {
+                canvas.bind();
+                unsafe {
+                    gl::Disable(gl::SCISSOR_TEST);
+                }
+                clear(ctx.clear_color);
+                canvas.viewport()
+            }
+            None => {
+                unsafe {
+                    gl::Disable(gl::SCISSOR_TEST);
+                }
+                clear(Color::black());
+                unsafe {
+                    gl::Enable(gl::SCISSOR_TEST);
+                }
+                clear(ctx.clear_color);
+                screen_viewport
+            }
+        };
+
+        render_cameras(&mut ctx, game, canvas.as_ref(), area)?;
+
+        if let Some(canvas) = &canvas {
+            // Post passes and the blit cover whole targets
+            unsafe {
+                gl::Disable(gl::SCISSOR_TEST);
+            }
+
+            let output = ctx
+                .post_process
+                .apply(&ctx.shader_manager, canvas, ctx.time.elapsed())?;
+
+            RenderTarget::unbind();
+            clear(Color::black());
+            output.unwrap_or(canvas).blit_to_screen(&screen_viewport);
+        }
+
+        if let Some(window) = &ctx.window {
+            window.gl_swap_window();
+        }
+
+        if let Some(frame_duration) = frame_duration {
+            let frame_time = frame_start.elapsed();
+            if frame_time < frame_duration {
+                std::thread::sleep(frame_duration - frame_time);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run the fixed updates due this frame, returns false once the loop should stop
+fn simulate<G: Game>(ctx: &mut Context, game: &mut G, max_frames: Option<u64>) -> bool {
+    if let Some(max_frames) = max_frames {
+        if ctx.time.frame_count() >= max_frames {
+            return false;
+        }
+    }
+
+    ctx.time.begin_frame();
+    let fixed_delta = ctx.time.fixed_delta();
+
+    let mut updated = false;
+    while ctx.time.step() {
+        game.update(ctx, fixed_delta);
+        ctx.update_cameras(fixed_delta);
+        updated = true;
+
+        if ctx.should_quit() {
+            return false;
+        }
+    }
+
+    // Keep input transitions around until an update got to see them
+    if updated {
+        ctx.input.end_frame();
+    }
+
+    true
+}
+
+fn clear(color: Color) {
+    unsafe {
+        let (r, g, b, a) = color.as_tuple();
+        gl::ClearColor(r, g, b, a);
+
+        gl::Clear(gl::COLOR_BUFFER_BIT);
+    }
+}
+
+/// Call `Game::render` once per enabled camera, in their order, each one
+/// drawing into its viewport of `area` or of its own target. `base` is the
+/// target `area` belongs to, `None` for the window
+fn render_cameras<G: Game>(
+    ctx: &mut Context,
+    game: &mut G,
+    base: Option<&RenderTarget>,
+    area: Viewport,
+) -> Result<()> {
+    let bind_base = || match base {
+        Some(base) => base.bind(),
+        None => RenderTarget::unbind(),
+    };
+
+    let headless = ctx.is_headless();
+    if !headless {
+        unsafe {
+            // Keeps the clear of each camera inside its viewport
+            gl::Enable(gl::SCISSOR_TEST);
+        }
+    }
+
+    ctx.batch.begin(Matrix4x4::identity());
+
+    // By name, an earlier `Game::render` call may have removed a camera
+    for camera in ctx.render_order() {
+        let view = match ctx.camera_named(camera.as_deref()) {
+            Some(view) => view,
+            None => continue,
+        };
+        let view_projection = view.view_projection();
+        let layers = view.layers;
+        let rect = view.viewport;
+        let target = view.target.clone();
+        let clear_color = view.clear_color;
+
+        if !headless {
+            let target_area = match &target {
+                Some(target) => {
+                    target.bind();
+                    target.viewport()
+                }
+                None => {
+                    bind_base();
+                    area
+                }
+            };
+            rect.to_pixels(&target_area).apply();
+
+            if let Some(color) = clear_color {
+                clear(color);
+            }
+
+            // Sprites draw with the shader of their material, any of them can need the camera
+            for shader in ctx.shader_manager.iter() {
+                if let Ok(location) = shader.get_uniform_location("u_projection") {
+                    shader.use_shader();
+                    unsafe {
+                        gl::UniformMatrix4fv(
+                            location, // uniform position (u_projection)
+                            1,
+                            gl::FALSE,
+                            view_projection.data.as_ptr(),
+                        );
+                    }
+                }
+            }
+            ctx.shader_manager.get("basic")?.use_shader();
+        }
+
+        ctx.batch.set_projection(view_projection);
+        ctx.batch.set_layers(layers);
+        ctx.set_rendering_camera(camera);
+
+        game.render(ctx);
+
+        // Before the next camera switches target or viewport
+        ctx.batch.flush();
+    }
+
+    ctx.set_rendering_camera(None);
+    ctx.batch.end();
+
+    if !headless {
+        bind_base();
+        area.apply();
+    }
+
+    Ok(())
+}
+
+/// Fit the virtual resolution in the window, keeping its aspect ratio.
+/// Returns the area of the window showing the game.
+fn resize(ctx: &mut Context, canvas: CanvasMode) -> Viewport {
+    let virtual_size = (ctx.virtual_width, ctx.virtual_height);
+    let fit = |screen_size| match canvas {
+        CanvasMode::IntegerScale => Viewport::integer_scale(screen_size, virtual_size),
+        _ => Viewport::letterbox(screen_size, virtual_size),
+    };
+
+    let window = match &ctx.window {
+        Some(window) => window,
+        None => return Viewport::default(),
+    };
+
+    let viewport = fit(window.drawable_size());
+    // With a canvas, the viewport is set when binding it
+    if canvas == CanvasMode::Off {
+        viewport.apply();
+    }
+
+    // Mouse events are reported in window coordinates, which differ from
+    // drawable pixels on high DPI screens
+    ctx.input.set_viewport(fit(window.size()), virtual_size);
+
+    viewport
+}
+
+fn get_display_mode(video_subsystem: &VideoSubsystem, config: &Config) -> Result<DisplayMode> {
+    let display_modes = video_subsystem
+        .num_display_modes(0)
+        .map_err(JellyError::Sdl)?;
+
+    for i in 0..display_modes {
+        let display_mode = video_subsystem
+            .display_mode(0, i)
+            .map_err(JellyError::Sdl)?;
+        if display_mode.w == config.screen_width as i32
+            && display_mode.h == config.screen_height as i32
+        {
+            return Ok(display_mode);
+        }
+    }
+
+    Err(JellyError::DisplayMode {
+        width: config.screen_width,
+        height: config.screen_height,
+    })
+}
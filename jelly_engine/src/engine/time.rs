@@ -0,0 +1,311 @@
+use std::{
+    cell::Cell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+/// Frames longer than this are clamped so a stall (debugger, window drag...)
+/// doesn't queue up hundreds of updates (spiral of death)
+const DEFAULT_MAX_FRAME_TIME: Duration = Duration::from_millis(250);
+
+/// Source of time for the game loop
+pub trait Clock {
+    /// Time elapsed since an arbitrary but fixed point
+    fn now(&self) -> Duration;
+}
+
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Clock that only moves when told to, so the loop can be stepped deterministically.
+/// Clones share the same time, keep one to drive a clock handed to `Time`.
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    now: Rc<Cell<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+
+    pub fn set(&self, now: Duration) {
+        self.now.set(now);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+/// Frame timing and fixed update accumulator.
+///
+/// Each frame calls `begin_frame` once, then runs one simulation update per
+/// successful `step`. What remains in the accumulator is exposed as `alpha`
+/// to interpolate rendering between the last two simulation states.
+pub struct Time {
+    clock: Box<dyn Clock>,
+
+    fixed_delta: Duration,
+    max_frame_time: Duration,
+    accumulator: Duration,
+    last_frame: Duration,
+
+    delta: Duration,
+    elapsed: Duration,
+    frame_count: u64,
+    update_count: u64,
+
+    fps: f32,
+    fps_timer: Duration,
+    fps_frames: u32,
+}
+
+impl Time {
+    pub fn new(update_rate: u32) -> Time {
+        Time::with_clock(update_rate, Box::new(SystemClock::new()))
+    }
+
+    pub fn with_clock(update_rate: u32, clock: Box<dyn Clock>) -> Time {
+        let last_frame = clock.now();
+
+        Time {
+            clock,
+
+            fixed_delta: Duration::from_secs_f64(1.0 / update_rate.max(1) as f64),
+            max_frame_time: DEFAULT_MAX_FRAME_TIME,
+            accumulator: Duration::from_secs(0),
+            last_frame,
+
+            delta: Duration::from_secs(0),
+            elapsed: Duration::from_secs(0),
+            frame_count: 0,
+            update_count: 0,
+
+            fps: 0.0,
+            fps_timer: Duration::from_secs(0),
+            fps_frames: 0,
+        }
+    }
+
+    pub fn set_max_frame_time(&mut self, max_frame_time: Duration) {
+        self.max_frame_time = max_frame_time;
+    }
+
+    /// Forget the time spent since the last frame, e.g. after loading
+    pub fn reset(&mut self) {
+        self.last_frame = self.clock.now();
+        self.accumulator = Duration::from_secs(0);
+    }
+
+    /// Sample the clock and feed the elapsed time to the accumulator
+    pub fn begin_frame(&mut self) {
+        let now = self.clock.now();
+        let frame_time = now
+            .checked_sub(self.last_frame)
+            .unwrap_or_default()
+            .min(self.max_frame_time);
+        self.last_frame = now;
+
+        self.delta = frame_time;
+        self.elapsed += frame_time;
+        self.accumulator += frame_time;
+        self.frame_count += 1;
+
+        self.fps_frames += 1;
+        self.fps_timer += frame_time;
+        if self.fps_timer >= Duration::from_secs(1) {
+            self.fps = self.fps_frames as f32 / self.fps_timer.as_secs_f32();
+            self.fps_frames = 0;
+            self.fps_timer = Duration::from_secs(0);
+        }
+    }
+
+    /// Consume one fixed update from the accumulator, returns false when
+    /// there isn't enough time left for another one
+    pub fn step(&mut self) -> bool {
+        if self.accumulator >= self.fixed_delta {
+            self.accumulator -= self.fixed_delta;
+            self.update_count += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Seconds since last frame (clamped to the max frame time)
+    pub fn delta(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// Seconds simulated by a single `Game::update`
+    pub fn fixed_delta(&self) -> f32 {
+        self.fixed_delta.as_secs_f32()
+    }
+
+    pub fn fixed_duration(&self) -> Duration {
+        self.fixed_delta
+    }
+
+    /// Seconds since the loop started
+    pub fn elapsed(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn update_count(&self) -> u64 {
+        self.update_count
+    }
+
+    /// Frames per second measured over the last full second
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+
+    /// Progress between the previous and the next fixed update, in 0..1
+    pub fn alpha(&self) -> f32 {
+        self.accumulator.as_secs_f32() / self.fixed_delta.as_secs_f32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    // 10 updates per second, so one every 100ms
+    fn manual_time() -> (ManualClock, Time) {
+        let clock = ManualClock::new();
+        let time = Time::with_clock(10, Box::new(clock.clone()));
+
+        (clock, time)
+    }
+
+    fn frame(clock: &ManualClock, time: &mut Time, duration: Duration) -> u32 {
+        clock.advance(duration);
+        time.begin_frame();
+
+        let mut steps = 0;
+        while time.step() {
+            steps += 1;
+        }
+
+        steps
+    }
+
+    #[test]
+    fn fixed_steps_per_frame() {
+        let (clock, mut time) = manual_time();
+
+        assert_eq!(frame(&clock, &mut time, ms(100)), 1);
+        assert_eq!(frame(&clock, &mut time, ms(230)), 2);
+        assert_eq!(frame(&clock, &mut time, ms(0)), 0);
+        assert_eq!(time.update_count(), 3);
+        assert_eq!(time.frame_count(), 3);
+        assert_eq!(time.delta(), 0.0);
+        assert!((time.elapsed() - 0.33).abs() < 1e-6);
+    }
+
+    #[test]
+    fn leftover_time_carries_over() {
+        let (clock, mut time) = manual_time();
+
+        assert_eq!(frame(&clock, &mut time, ms(60)), 0);
+        assert_eq!(frame(&clock, &mut time, ms(60)), 1);
+        assert!((time.alpha() - 0.2).abs() < 1e-4);
+    }
+
+    #[test]
+    fn alpha() {
+        let (clock, mut time) = manual_time();
+
+        frame(&clock, &mut time, ms(25));
+        assert!((time.alpha() - 0.25).abs() < 1e-4);
+
+        frame(&clock, &mut time, ms(50));
+        assert!((time.alpha() - 0.75).abs() < 1e-4);
+
+        frame(&clock, &mut time, ms(25));
+        assert!(time.alpha().abs() < 1e-4);
+    }
+
+    #[test]
+    fn long_frames_are_clamped() {
+        let (clock, mut time) = manual_time();
+
+        assert_eq!(frame(&clock, &mut time, ms(10_000)), 2);
+        assert!((time.delta() - 0.25).abs() < 1e-6);
+        assert!((time.alpha() - 0.5).abs() < 1e-4);
+
+        time.set_max_frame_time(ms(1_000));
+        assert_eq!(frame(&clock, &mut time, ms(10_000)), 10);
+    }
+
+    #[test]
+    fn reset_forgets_the_time_spent() {
+        let (clock, mut time) = manual_time();
+
+        frame(&clock, &mut time, ms(50));
+        clock.advance(ms(200));
+        time.reset();
+
+        assert_eq!(frame(&clock, &mut time, ms(50)), 0);
+        assert!((time.alpha() - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn fps_over_the_last_second() {
+        let (clock, mut time) = manual_time();
+
+        for _ in 0..19 {
+            frame(&clock, &mut time, ms(50));
+        }
+        assert_eq!(time.fps(), 0.0);
+
+        frame(&clock, &mut time, ms(50));
+        assert!((time.fps() - 20.0).abs() < 1e-3);
+
+        // Kept until the next full second
+        for _ in 0..5 {
+            frame(&clock, &mut time, ms(100));
+        }
+        assert!((time.fps() - 20.0).abs() < 1e-3);
+        for _ in 0..5 {
+            frame(&clock, &mut time, ms(100));
+        }
+        assert!((time.fps() - 10.0).abs() < 1e-3);
+    }
+}
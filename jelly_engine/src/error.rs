@@ -0,0 +1,179 @@
+use std::{fmt, path::PathBuf};
+
+use crate::graphics::prelude::HtmlColorConversionError;
+
+pub type Result<T> = std::result::Result<T, JellyError>;
+
+#[derive(Debug)]
+pub enum JellyError {
+    /// SDL failed to initialize a subsystem, window or context
+    Sdl(String),
+    /// No display mode matches the requested screen size
+    DisplayMode {
+        width: u32,
+        height: u32,
+    },
+    /// A GL resource was requested while running headless
+    NoGraphicsContext,
+    /// A global logger was already installed
+    LoggerAlreadySet,
+
+    AssetNotFound(PathBuf),
+    ImageDecode {
+        name: String,
+        source: image::ImageError,
+    },
+    Io(std::io::Error),
+
+    ShaderManagerAlreadyAlive,
+    ShaderNotFound(String),
+    ShaderCompile {
+        shader: String,
+        log: String,
+    },
+    ShaderLink {
+        shader: String,
+        log: String,
+    },
+    MissingAttribute {
+        shader: String,
+        name: String,
+    },
+    MissingUniform {
+        shader: String,
+        name: String,
+    },
+    /// A value was set to a uniform of another GLSL type
+    UniformTypeMismatch {
+        shader: String,
+        name: String,
+    },
+    /// A write went past the end of a GL buffer, counted in bytes
+    BufferOverflow {
+        offset: usize,
+        len: usize,
+        capacity: usize,
+    },
+
+    /// Framebuffer creation failed with the given `glCheckFramebufferStatus` value
+    IncompleteFramebuffer(u32),
+    /// Raw RGBA8 pixels don't cover a whole texture
+    PixelDataSize {
+        width: u32,
+        height: u32,
+        len: usize,
+    },
+    TextureNotFound(String),
+    RegionNotFound(String),
+    /// A texture atlas definition couldn't be parsed
+    InvalidAtlas(String),
+    AnimationNotFound(String),
+    /// A tile map couldn't be read
+    InvalidTilemap(String),
+    /// Autotiling rules couldn't be parsed
+    InvalidAutotileRules(String),
+    /// A font descriptor couldn't be read
+    InvalidFont(String),
+    /// Text markup couldn't be parsed
+    InvalidMarkup(String),
+
+    /// Input bindings couldn't be parsed or serialized
+    InvalidBindings(String),
+
+    InvalidColor(String),
+    ColorConversion(HtmlColorConversionError),
+}
+
+impl fmt::Display for JellyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JellyError::Sdl(e) => write!(f, "SDL error: {}", e),
+            JellyError::DisplayMode { width, height } => write!(
+                f,
+                "No display mode available for aspect ratio {}x{}",
+                width, height
+            ),
+            JellyError::NoGraphicsContext => write!(f, "No graphics context available"),
+            JellyError::LoggerAlreadySet => write!(f, "A logger is already set"),
+            JellyError::AssetNotFound(path) => write!(f, "Asset not found: {}", path.display()),
+            JellyError::ImageDecode { name, source } => {
+                write!(f, "Could not load image {}: {}", name, source)
+            }
+            JellyError::Io(e) => write!(f, "IO error: {}", e),
+            JellyError::ShaderManagerAlreadyAlive => {
+                write!(f, "Cannot create two instance of ShaderManager")
+            }
+            JellyError::ShaderNotFound(name) => write!(f, "Unable to find shader {}", name),
+            JellyError::ShaderCompile { shader, log } => {
+                write!(f, "Failed to compile shader {}: {}", shader, log)
+            }
+            JellyError::ShaderLink { shader, log } => {
+                write!(f, "Failed to link shader {}: {}", shader, log)
+            }
+            JellyError::MissingAttribute { shader, name } => {
+                write!(f, "Unable to find attribute {} in shader {}", name, shader)
+            }
+            JellyError::MissingUniform { shader, name } => {
+                write!(f, "Unable to find uniform {} in shader {}", name, shader)
+            }
+            JellyError::UniformTypeMismatch { shader, name } => write!(
+                f,
+                "Uniform {} of shader {} doesn't have the type of its value",
+                name, shader
+            ),
+            JellyError::BufferOverflow {
+                offset,
+                len,
+                capacity,
+            } => write!(
+                f,
+                "Cannot write {} bytes at {} in a buffer of {}",
+                len, offset, capacity
+            ),
+            JellyError::IncompleteFramebuffer(status) => {
+                write!(f, "Incomplete framebuffer (status 0x{:x})", status)
+            }
+            JellyError::PixelDataSize { width, height, len } => write!(
+                f,
+                "A {}x{} texture needs {} bytes of RGBA8 pixels, got {}",
+                width,
+                height,
+                *width as usize * *height as usize * 4,
+                len
+            ),
+            JellyError::TextureNotFound(name) => write!(f, "Unable to find texture {}", name),
+            JellyError::RegionNotFound(name) => write!(f, "Unable to find texture region {}", name),
+            JellyError::InvalidAtlas(e) => write!(f, "Invalid texture atlas: {}", e),
+            JellyError::AnimationNotFound(name) => write!(f, "Unable to find animation {}", name),
+            JellyError::InvalidTilemap(e) => write!(f, "Invalid tile map: {}", e),
+            JellyError::InvalidAutotileRules(e) => write!(f, "Invalid autotile rules: {}", e),
+            JellyError::InvalidFont(e) => write!(f, "Invalid font: {}", e),
+            JellyError::InvalidMarkup(e) => write!(f, "Invalid text markup: {}", e),
+            JellyError::InvalidBindings(e) => write!(f, "Invalid input bindings: {}", e),
+            JellyError::InvalidColor(name) => write!(f, "Invalid color {}", name),
+            JellyError::ColorConversion(e) => write!(f, "Invalid hex color: {:?}", e),
+        }
+    }
+}
+
+impl std::error::Error for JellyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JellyError::ImageDecode { source, .. } => Some(source),
+            JellyError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for JellyError {
+    fn from(e: std::io::Error) -> Self {
+        JellyError::Io(e)
+    }
+}
+
+impl From<HtmlColorConversionError> for JellyError {
+    fn from(e: HtmlColorConversionError) -> Self {
+        JellyError::ColorConversion(e)
+    }
+}
@@ -0,0 +1,85 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::Level;
+
+/// Target used for every message reported by the GL driver
+pub const GL_LOG_TARGET: &str = "jelly_engine::gl";
+
+// Notifications are very chatty (buffer usage hints...), they're dropped unless asked for
+static LOG_NOTIFICATIONS: AtomicBool = AtomicBool::new(false);
+
+/// Route GL debug messages to the logger
+pub fn enable_debug_output(log_notifications: bool) {
+    LOG_NOTIFICATIONS.store(log_notifications, Ordering::Relaxed);
+
+    // Crash on macOS
+    if cfg!(target_os = "macos") {
+        return;
+    }
+
+    unsafe {
+        gl::Enable(gl::DEBUG_OUTPUT);
+        gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+        gl::DebugMessageCallback(Some(dbg_callback), std::ptr::null());
+    }
+}
+
+extern "system" fn dbg_callback(
+    source: gl::types::GLenum,
+    etype: gl::types::GLenum,
+    id: gl::types::GLuint,
+    severity: gl::types::GLenum,
+    _msg_length: gl::types::GLsizei,
+    msg: *const gl::types::GLchar,
+    _user_data: *mut std::ffi::c_void,
+) {
+    if severity == gl::DEBUG_SEVERITY_NOTIFICATION && !LOG_NOTIFICATIONS.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let msg = unsafe { std::ffi::CStr::from_ptr(msg) };
+
+    log::log!(
+        target: GL_LOG_TARGET,
+        severity_level(severity),
+        "[{}] [{}] ({}) {}",
+        source_name(source),
+        type_name(etype),
+        id,
+        msg.to_string_lossy(),
+    );
+}
+
+pub fn severity_level(severity: gl::types::GLenum) -> Level {
+    match severity {
+        gl::DEBUG_SEVERITY_HIGH => Level::Error,
+        gl::DEBUG_SEVERITY_MEDIUM => Level::Warn,
+        gl::DEBUG_SEVERITY_LOW => Level::Info,
+        _ => Level::Debug,
+    }
+}
+
+pub fn source_name(source: gl::types::GLenum) -> &'static str {
+    match source {
+        gl::DEBUG_SOURCE_API => "API",
+        gl::DEBUG_SOURCE_WINDOW_SYSTEM => "Window system",
+        gl::DEBUG_SOURCE_SHADER_COMPILER => "Shader compiler",
+        gl::DEBUG_SOURCE_THIRD_PARTY => "Third party",
+        gl::DEBUG_SOURCE_APPLICATION => "Application",
+        _ => "Other",
+    }
+}
+
+pub fn type_name(etype: gl::types::GLenum) -> &'static str {
+    match etype {
+        gl::DEBUG_TYPE_ERROR => "Error",
+        gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "Deprecated behavior",
+        gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "Undefined behavior",
+        gl::DEBUG_TYPE_PORTABILITY => "Portability",
+        gl::DEBUG_TYPE_PERFORMANCE => "Performance",
+        gl::DEBUG_TYPE_MARKER => "Marker",
+        gl::DEBUG_TYPE_PUSH_GROUP => "Push group",
+        gl::DEBUG_TYPE_POP_GROUP => "Pop group",
+        _ => "Other",
+    }
+}
@@ -1,35 +1,108 @@
+use super::vertex_layout::{AttributeType, VertexAttribute, VertexFormat, VertexLayout};
+use crate::error::{JellyError, Result};
+
+/// Float attribute laid out right after the previous one, see `GLbuffer::configure`
 pub struct AttributeInfo {
     pub location: gl::types::GLuint,
     pub component_size: gl::types::GLint,
 }
 
+/// How often the buffer content is expected to change
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BufferUsage {
+    /// Uploaded once, drawn many times
+    Static,
+    /// Updated now and then, drawn many times
+    Dynamic,
+    /// Updated every time it's drawn
+    Stream,
+}
+
+impl BufferUsage {
+    fn gl_enum(self) -> gl::types::GLenum {
+        match self {
+            BufferUsage::Static => gl::STATIC_DRAW,
+            BufferUsage::Dynamic => gl::DYNAMIC_DRAW,
+            BufferUsage::Stream => gl::STREAM_DRAW,
+        }
+    }
+}
+
+/// Primitive assembled from the vertices
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DrawMode {
+    Points,
+    Lines,
+    LineStrip,
+    LineLoop,
+    Triangles,
+    TriangleStrip,
+    TriangleFan,
+}
+
+impl DrawMode {
+    fn gl_enum(self) -> gl::types::GLenum {
+        match self {
+            DrawMode::Points => gl::POINTS,
+            DrawMode::Lines => gl::LINES,
+            DrawMode::LineStrip => gl::LINE_STRIP,
+            DrawMode::LineLoop => gl::LINE_LOOP,
+            DrawMode::Triangles => gl::TRIANGLES,
+            DrawMode::TriangleStrip => gl::TRIANGLE_STRIP,
+            DrawMode::TriangleFan => gl::TRIANGLE_FAN,
+        }
+    }
+}
+
+/// Vertex array with its vertex buffer and an optional index buffer.
+///
+/// Vertices are uploaded as slices of any `Copy` type matching the layout
+/// given to `configure_layout`, e.g. a `#[repr(C)]` struct implementing `VertexFormat`.
 pub struct GLbuffer {
-    type_size: usize,
-    element_size: gl::types::GLint,
+    // Sizes in bytes
+    stride: usize,
     data_len: usize,
-    stride: gl::types::GLint,
+    capacity: usize,
+    index_count: usize,
+    usage: BufferUsage,
+    mode: DrawMode,
     vao: gl::types::GLuint,
     vbo: gl::types::GLuint,
+    // 0 until indices are uploaded
+    ebo: gl::types::GLuint,
 }
 
 impl Drop for GLbuffer {
     fn drop(&mut self) {
         unsafe {
+            if self.ebo != 0 {
+                gl::DeleteBuffers(1, &self.ebo);
+            }
             gl::DeleteBuffers(1, &self.vbo);
             gl::DeleteVertexArrays(1, &self.vao)
         }
     }
 }
 
+impl Default for GLbuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl GLbuffer {
+    /// Static buffer of triangles
     pub fn new() -> GLbuffer {
         let mut gl_buffer = GLbuffer {
-            type_size: std::mem::size_of::<f32>(),
-            element_size: 0,
-            data_len: 0,
             stride: 0,
+            data_len: 0,
+            capacity: 0,
+            index_count: 0,
+            usage: BufferUsage::Static,
+            mode: DrawMode::Triangles,
             vao: 0,
             vbo: 0,
+            ebo: 0,
         };
 
         unsafe {
@@ -40,31 +113,92 @@ impl GLbuffer {
         gl_buffer
     }
 
+    pub fn with_usage(usage: BufferUsage) -> GLbuffer {
+        let mut gl_buffer = GLbuffer::new();
+        gl_buffer.usage = usage;
+
+        gl_buffer
+    }
+
+    /// Usage hint of the next allocations
+    pub fn set_usage(&mut self, usage: BufferUsage) {
+        self.usage = usage;
+    }
+
+    pub fn set_mode(&mut self, mode: DrawMode) {
+        self.mode = mode;
+    }
+
+    pub fn mode(&self) -> DrawMode {
+        self.mode
+    }
+
+    /// Number of vertices uploaded
+    pub fn vertex_count(&self) -> usize {
+        if self.stride == 0 {
+            return 0;
+        }
+
+        self.data_len / self.stride
+    }
+
+    pub fn index_count(&self) -> usize {
+        self.index_count
+    }
+
+    /// Tightly packed float attributes, uploaded as `&[f32]`
     pub fn configure(&mut self, attributes: Vec<AttributeInfo>, normalized: bool) {
+        let mut layout = VertexLayout::new(0);
+        let mut locations = Vec::with_capacity(attributes.len());
+
+        for attribute in &attributes {
+            layout.attributes.push(VertexAttribute {
+                name: String::new(),
+                kind: AttributeType::Float,
+                count: attribute.component_size,
+                normalized,
+                integer: false,
+                offset: layout.stride,
+            });
+            layout.stride += attribute.component_size as usize * AttributeType::Float.size();
+            locations.push(attribute.location);
+        }
+
+        self.configure_layout(&layout, &locations);
+    }
+
+    /// Describe the vertices, `locations` holds the shader location of each
+    /// attribute of `layout`, see `VertexLayout::locations`
+    pub fn configure_layout(&mut self, layout: &VertexLayout, locations: &[gl::types::GLuint]) {
+        self.stride = layout.stride;
+
         unsafe {
             gl::BindVertexArray(self.vao);
             gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
 
-            self.element_size = attributes
-                .iter()
-                .map(|attribute| attribute.component_size)
-                .sum();
-            self.stride = self.element_size * self.type_size as gl::types::GLint;
-
-            let mut offset = 0;
-
-            for attribute in &attributes {
-                gl::VertexAttribPointer(
-                    attribute.location, // index of the generic vertex attribute ("e.g. layout (location = 0)")
-                    attribute.component_size, // number of components per vertex attribute
-                    gl::FLOAT,          // data type
-                    normalized as gl::types::GLboolean, // normalized
-                    self.stride,        // stride (byte offset between consecutive attributes)
-                    offset as *const std::ffi::c_void, // offset in byte
-                );
-                gl::EnableVertexAttribArray(attribute.location);
+            for (attribute, &location) in layout.attributes.iter().zip(locations) {
+                let stride = layout.stride as gl::types::GLsizei; // byte offset between consecutive vertices
+                let offset = attribute.offset as *const std::ffi::c_void; // offset in byte
 
-                offset += attribute.component_size * self.type_size as i32;
+                if attribute.integer {
+                    gl::VertexAttribIPointer(
+                        location,
+                        attribute.count,
+                        attribute.kind.gl_enum(),
+                        stride,
+                        offset,
+                    );
+                } else {
+                    gl::VertexAttribPointer(
+                        location,        // index of the generic vertex attribute ("e.g. layout (location = 0)")
+                        attribute.count, // number of components per vertex attribute
+                        attribute.kind.gl_enum(), // data type
+                        attribute.normalized as gl::types::GLboolean, // normalized
+                        stride,
+                        offset,
+                    );
+                }
+                gl::EnableVertexAttribArray(location);
             }
 
             gl::BindBuffer(gl::ARRAY_BUFFER, 0);
@@ -72,28 +206,159 @@ impl GLbuffer {
         }
     }
 
-    pub fn upload(&mut self, data: &[f32]) {
-        self.data_len = data.len();
+    /// Configure the buffer for vertices of type `V`
+    pub fn configure_for<V: VertexFormat>(&mut self, locations: &[gl::types::GLuint]) {
+        self.configure_layout(&V::layout(), locations);
+    }
+
+    /// Replace the vertex data.
+    ///
+    /// Dynamic and stream buffers keep their allocation when `data` fits in it:
+    /// the old storage is orphaned and the data written with `BufferSubData`,
+    /// so the driver doesn't have to wait for draws still using it.
+    pub fn upload<T: Copy>(&mut self, data: &[T]) {
+        let size = std::mem::size_of_val(data);
+
+        if self.usage != BufferUsage::Static && size <= self.capacity {
+            self.orphan();
+            self.write(0, data);
+        } else {
+            self.allocate(size, data.as_ptr() as *const gl::types::GLvoid);
+        }
+
+        self.data_len = size;
+    }
+
+    /// Allocate room for `count` values of `T` without uploading anything
+    pub fn reserve<T>(&mut self, count: usize) {
+        let size = count * std::mem::size_of::<T>();
+        if size > self.capacity {
+            self.allocate(size, std::ptr::null());
+        }
+    }
+
+    /// Overwrite part of the vertex data, starting at the `offset`th value of `T`
+    pub fn update<T: Copy>(&mut self, offset: usize, data: &[T]) -> Result<()> {
+        let offset = offset * std::mem::size_of::<T>();
+        let size = std::mem::size_of_val(data);
+
+        if offset + size > self.capacity {
+            return Err(JellyError::BufferOverflow {
+                offset,
+                len: size,
+                capacity: self.capacity,
+            });
+        }
+
+        self.write(offset, data);
+        self.data_len = self.data_len.max(offset + size);
+
+        Ok(())
+    }
+
+    /// Give the current storage back to the driver and get a fresh one of the same size,
+    /// its content is undefined until written again
+    pub fn orphan(&mut self) {
+        self.data_len = 0;
+
         unsafe {
             gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
             gl::BufferData(
                 gl::ARRAY_BUFFER,
-                (self.data_len * self.type_size) as gl::types::GLsizeiptr, // size of data in bytes,
-                data.as_ptr() as *const gl::types::GLvoid,                 // pointer to data
-                gl::STATIC_DRAW,                                           // usage
+                self.capacity as gl::types::GLsizeiptr,
+                std::ptr::null(),
+                self.usage.gl_enum(),
             );
             gl::BindBuffer(gl::ARRAY_BUFFER, 0);
         }
     }
 
+    /// Draw with `indices` into the vertices instead of the vertices in order
+    pub fn upload_indices(&mut self, indices: &[u32]) {
+        self.index_count = indices.len();
+
+        unsafe {
+            if self.ebo == 0 {
+                gl::GenBuffers(1, &mut self.ebo);
+            }
+
+            // The element buffer binding is part of the vertex array state
+            gl::BindVertexArray(self.vao);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.ebo);
+            gl::BufferData(
+                gl::ELEMENT_ARRAY_BUFFER,
+                std::mem::size_of_val(indices) as gl::types::GLsizeiptr,
+                indices.as_ptr() as *const gl::types::GLvoid,
+                self.usage.gl_enum(),
+            );
+            gl::BindVertexArray(0);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, 0);
+        }
+    }
+
+    /// Draw every vertex, or every index when indices were uploaded
     pub fn draw(&self) {
+        let count = if self.ebo != 0 {
+            self.index_count
+        } else {
+            self.vertex_count()
+        };
+
+        self.draw_range(0, count);
+    }
+
+    /// Draw `count` indices (or vertices when not indexed) starting at `first`
+    pub fn draw_range(&self, first: usize, count: usize) {
+        if count == 0 {
+            return;
+        }
+
         unsafe {
             gl::BindVertexArray(self.vao);
-            gl::DrawArrays(
-                gl::TRIANGLES,                                         // mode
-                0, // starting index in the enabled arrays
-                self.data_len as gl::types::GLint / self.element_size, // number of indices
-            )
+
+            if self.ebo != 0 {
+                gl::DrawElements(
+                    self.mode.gl_enum(),                                              // mode
+                    count as gl::types::GLsizei, // number of indices
+                    gl::UNSIGNED_INT,            // index type
+                    (first * std::mem::size_of::<u32>()) as *const gl::types::GLvoid, // offset in byte
+                );
+            } else {
+                gl::DrawArrays(
+                    self.mode.gl_enum(),         // mode
+                    first as gl::types::GLint,   // starting index in the enabled arrays
+                    count as gl::types::GLsizei, // number of vertices
+                );
+            }
+        }
+    }
+
+    fn allocate(&mut self, size: usize, data: *const gl::types::GLvoid) {
+        self.capacity = size;
+
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                size as gl::types::GLsizeiptr, // size of data in bytes
+                data,                          // pointer to data
+                self.usage.gl_enum(),          // usage
+            );
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+    }
+
+    /// `offset` in bytes
+    fn write<T: Copy>(&self, offset: usize, data: &[T]) {
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                offset as gl::types::GLintptr, // offset in bytes
+                std::mem::size_of_val(data) as gl::types::GLsizeiptr, // size of data in bytes
+                data.as_ptr() as *const gl::types::GLvoid,
+            );
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
         }
     }
 }
@@ -1,7 +1,10 @@
+pub mod debug;
 pub mod gl_buffer;
 pub mod shader;
+pub mod vertex_layout;
 
 pub mod prelude {
     pub use crate::gl_utilities::gl_buffer::*;
     pub use crate::gl_utilities::shader::*;
+    pub use crate::gl_utilities::vertex_layout::*;
 }
@@ -4,27 +4,50 @@ use std::{
     sync::atomic::{AtomicBool, Ordering},
 };
 
+use crate::error::{JellyError, Result};
+use crate::graphics::prelude::Color;
+use crate::math::prelude::{Matrix4x4, Vec2, Vec3};
+
 /// Only one ShaderManager can be alive
 // Set to false by default (not alive)
 static IS_SHADER_MANAGER_ALIVE: AtomicBool = AtomicBool::new(false);
 
 pub struct ShaderManager {
     shaders: HashMap<String, Shader>,
+    headless: bool,
 }
 
 impl ShaderManager {
-    pub fn init() -> ShaderManager {
+    pub fn init() -> Result<ShaderManager> {
         let was_alive = IS_SHADER_MANAGER_ALIVE.swap(true, Ordering::Relaxed);
         if !was_alive {
-            ShaderManager {
+            Ok(ShaderManager {
                 shaders: HashMap::new(),
-            }
+                headless: false,
+            })
         } else {
-            panic!("Cannot create two instance of ShaderManager")
+            Err(JellyError::ShaderManagerAlreadyAlive)
         }
     }
 
-    pub fn register(&mut self, name: &str, vertex_source: &str, fragment_source: &str) -> &Shader {
+    /// Empty manager for runs without a GL context, it doesn't count as the alive instance
+    pub(crate) fn headless() -> ShaderManager {
+        ShaderManager {
+            shaders: HashMap::new(),
+            headless: true,
+        }
+    }
+
+    pub fn register(
+        &mut self,
+        name: &str,
+        vertex_source: &str,
+        fragment_source: &str,
+    ) -> Result<&Shader> {
+        if self.headless {
+            return Err(JellyError::NoGraphicsContext);
+        }
+
         let mut shader = Shader {
             name: String::from(name),
             program: 0,
@@ -32,20 +55,79 @@ impl ShaderManager {
             uniforms: HashMap::new(),
         };
 
-        shader.load(
-            &CString::new(vertex_source).expect("CString::new failed"),
-            &CString::new(fragment_source).expect("CString::new failed"),
-        );
+        let to_cstring = |source: &str| {
+            CString::new(source).map_err(|e| JellyError::ShaderCompile {
+                shader: String::from(name),
+                log: e.to_string(),
+            })
+        };
+
+        shader.load(&to_cstring(vertex_source)?, &to_cstring(fragment_source)?)?;
 
         self.shaders.insert(String::from(name), shader);
 
         self.get(name)
     }
 
-    pub fn get(&self, name: &str) -> &Shader {
+    pub fn get(&self, name: &str) -> Result<&Shader> {
         match self.shaders.get(name) {
-            Some(shader) => shader,
-            _ => panic!("Unable to find shader {}", name),
+            Some(shader) => Ok(shader),
+            _ => Err(JellyError::ShaderNotFound(String::from(name))),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Shader> {
+        self.shaders.values()
+    }
+}
+
+impl Drop for ShaderManager {
+    fn drop(&mut self) {
+        if !self.headless {
+            IS_SHADER_MANAGER_ALIVE.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Value of a shader uniform
+#[derive(Debug, Copy, Clone)]
+pub enum UniformValue {
+    Float(f32),
+    Int(i32),
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Color(Color),
+    Matrix4x4(Matrix4x4),
+}
+
+impl UniformValue {
+    /// Whether the value can be set to a uniform of the GLSL type `gl_type`
+    pub fn fits(&self, gl_type: gl::types::GLenum) -> bool {
+        match self {
+            UniformValue::Float(_) => gl_type == gl::FLOAT,
+            UniformValue::Int(_) => matches!(gl_type, gl::INT | gl::BOOL | gl::SAMPLER_2D),
+            UniformValue::Vec2(_) => gl_type == gl::FLOAT_VEC2,
+            UniformValue::Vec3(_) => gl_type == gl::FLOAT_VEC3,
+            UniformValue::Color(_) => gl_type == gl::FLOAT_VEC4,
+            UniformValue::Matrix4x4(_) => gl_type == gl::FLOAT_MAT4,
+        }
+    }
+
+    /// Set the uniform at `location` of the program in use
+    pub fn apply(&self, location: gl::types::GLint) {
+        unsafe {
+            match self {
+                UniformValue::Float(value) => gl::Uniform1f(location, *value),
+                UniformValue::Int(value) => gl::Uniform1i(location, *value),
+                UniformValue::Vec2(value) => gl::Uniform2f(location, value.x, value.y),
+                UniformValue::Vec3(value) => gl::Uniform3f(location, value.x, value.y, value.z),
+                UniformValue::Color(value) => {
+                    gl::Uniform4f(location, value.r, value.g, value.b, value.a)
+                }
+                UniformValue::Matrix4x4(value) => {
+                    gl::UniformMatrix4fv(location, 1, gl::FALSE, value.data.as_ptr())
+                }
+            }
         }
     }
 }
@@ -54,7 +136,8 @@ pub struct Shader {
     pub name: String,
     pub program: gl::types::GLuint,
     attributes: HashMap<String, gl::types::GLuint>,
-    uniforms: HashMap<String, gl::types::GLint>,
+    // Location and GLSL type by name
+    uniforms: HashMap<String, (gl::types::GLint, gl::types::GLenum)>,
 }
 
 impl Shader {
@@ -64,38 +147,92 @@ impl Shader {
         }
     }
 
-    pub fn get_attribute_location(&self, name: &str) -> gl::types::GLuint {
+    pub fn get_attribute_location(&self, name: &str) -> Result<gl::types::GLuint> {
         match self.attributes.get(name) {
-            Some(&attribute) => attribute,
-            _ => panic!("Unable to find attribute {} in shader {}", name, self.name),
+            Some(&attribute) => Ok(attribute),
+            _ => Err(JellyError::MissingAttribute {
+                shader: self.name.clone(),
+                name: String::from(name),
+            }),
         }
     }
-    pub fn get_uniform_location(&self, name: &str) -> gl::types::GLint {
+    pub fn get_uniform_location(&self, name: &str) -> Result<gl::types::GLint> {
+        self.get_uniform(name).map(|(location, _)| location)
+    }
+
+    /// GLSL type of the uniform `name`, e.g. `gl::FLOAT_VEC4` or `gl::SAMPLER_2D`
+    pub fn get_uniform_type(&self, name: &str) -> Result<gl::types::GLenum> {
+        self.get_uniform(name).map(|(_, gl_type)| gl_type)
+    }
+
+    /// Location of the uniform `name`, if `value` can be set to it
+    pub fn check_uniform(&self, name: &str, value: &UniformValue) -> Result<gl::types::GLint> {
+        let (location, gl_type) = self.get_uniform(name)?;
+        if !value.fits(gl_type) {
+            return Err(JellyError::UniformTypeMismatch {
+                shader: self.name.clone(),
+                name: String::from(name),
+            });
+        }
+
+        Ok(location)
+    }
+
+    fn get_uniform(&self, name: &str) -> Result<(gl::types::GLint, gl::types::GLenum)> {
         match self.uniforms.get(name) {
-            Some(&uniform) => uniform,
-            _ => panic!("Unable to find uniform {} in shader {}", name, self.name),
+            Some(&uniform) => Ok(uniform),
+            _ => Err(JellyError::MissingUniform {
+                shader: self.name.clone(),
+                name: String::from(name),
+            }),
         }
     }
 
-    pub fn load(&mut self, vertex_source: &CString, fragment_source: &CString) {
-        let vertex_shader = Shader::load_shader(vertex_source, gl::VERTEX_SHADER).unwrap();
-        let fragment_shader = Shader::load_shader(fragment_source, gl::FRAGMENT_SHADER).unwrap();
+    /// Set the uniform `name`, the shader must be in use
+    pub fn set_uniform(&self, name: &str, value: &UniformValue) -> Result<()> {
+        value.apply(self.get_uniform_location(name)?);
 
-        self.program = Shader::create_program(&[vertex_shader, fragment_shader]).unwrap();
+        Ok(())
+    }
 
-        self.detect_attributes();
-        self.detect_uniforms();
+    pub fn load(&mut self, vertex_source: &CString, fragment_source: &CString) -> Result<()> {
+        let compile_error = |log| JellyError::ShaderCompile {
+            shader: self.name.clone(),
+            log,
+        };
+
+        let vertex_shader =
+            Shader::load_shader(vertex_source, gl::VERTEX_SHADER).map_err(compile_error)?;
+        let fragment_shader = match Shader::load_shader(fragment_source, gl::FRAGMENT_SHADER) {
+            Ok(fragment_shader) => fragment_shader,
+            Err(log) => {
+                unsafe { gl::DeleteShader(vertex_shader) };
+                return Err(compile_error(log));
+            }
+        };
+
+        let program = Shader::create_program(&[vertex_shader, fragment_shader]);
 
         unsafe {
             gl::DeleteShader(vertex_shader);
             gl::DeleteShader(fragment_shader);
         }
+
+        self.program = program.map_err(|log| JellyError::ShaderLink {
+            shader: self.name.clone(),
+            log,
+        })?;
+
+        self.detect_attributes();
+        self.detect_uniforms();
+
+        Ok(())
     }
 
     fn load_shader(
         source: &CString,
         shader_type: gl::types::GLenum,
-    ) -> Result<gl::types::GLuint, String> {
+    ) -> std::result::Result<gl::types::GLuint, String> {
         let id = unsafe { gl::CreateShader(shader_type) };
 
         unsafe {
@@ -117,6 +254,7 @@ impl Shader {
                     std::ptr::null_mut(),
                     error_msg.as_ptr() as *mut gl::types::GLchar,
                 );
+                gl::DeleteShader(id);
 
                 return Err(error_msg.to_string_lossy().into_owned());
             }
@@ -125,7 +263,9 @@ impl Shader {
         }
     }
 
-    fn create_program(shaders: &[gl::types::GLuint]) -> Result<gl::types::GLuint, String> {
+    fn create_program(
+        shaders: &[gl::types::GLuint],
+    ) -> std::result::Result<gl::types::GLuint, String> {
         let program_id = unsafe { gl::CreateProgram() };
 
         for shader in shaders {
@@ -158,6 +298,7 @@ impl Shader {
                     std::ptr::null_mut(),
                     error.as_ptr() as *mut gl::types::GLchar,
                 );
+                gl::DeleteProgram(program_id);
             }
 
             return Err(error.to_string_lossy().into_owned());
@@ -177,21 +318,28 @@ impl Shader {
             let mut attributes_number: gl::types::GLint = 0;
             gl::GetProgramiv(self.program, gl::ACTIVE_ATTRIBUTES, &mut attributes_number);
 
+            // Longest name, null terminator included
+            let mut max_length: gl::types::GLint = 0;
+            gl::GetProgramiv(
+                self.program,
+                gl::ACTIVE_ATTRIBUTE_MAX_LENGTH,
+                &mut max_length,
+            );
+
             for i in 0..attributes_number {
                 let mut size: gl::types::GLint = 0; // variable size
                 let mut var_type: gl::types::GLenum = 0; // variable type (e.g. float, vec3, vec4, mat4)
-                const BUF_SIZE: usize = 16; // maximum name length
-                let name = [0; BUF_SIZE];
+                let mut name: Vec<gl::types::GLchar> = vec![0; max_length.max(1) as usize];
                 let mut length: gl::types::GLsizei = 0; // name length
 
                 gl::GetActiveAttrib(
                     self.program,
                     i as gl::types::GLuint,
-                    BUF_SIZE as gl::types::GLint,
+                    name.len() as gl::types::GLint,
                     &mut length,
                     &mut size,
                     &mut var_type,
-                    name.as_ptr() as *mut gl::types::GLchar,
+                    name.as_mut_ptr(),
                 );
 
                 if length == 0 {
@@ -214,21 +362,24 @@ impl Shader {
             let mut uniforms_number: gl::types::GLint = 0;
             gl::GetProgramiv(self.program, gl::ACTIVE_UNIFORMS, &mut uniforms_number);
 
+            // Longest name, null terminator included
+            let mut max_length: gl::types::GLint = 0;
+            gl::GetProgramiv(self.program, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut max_length);
+
             for i in 0..uniforms_number {
                 let mut size: gl::types::GLint = 0; // variable size
                 let mut var_type: gl::types::GLenum = 0; // variable type (e.g. float, vec3, vec4, mat4)
-                const BUF_SIZE: usize = 16; // maximum name length
-                let name = [0; BUF_SIZE];
+                let mut name: Vec<gl::types::GLchar> = vec![0; max_length.max(1) as usize];
                 let mut length: gl::types::GLsizei = 0; // name length
 
                 gl::GetActiveUniform(
                     self.program,
                     i as gl::types::GLuint,
-                    BUF_SIZE as gl::types::GLint,
+                    name.len() as gl::types::GLint,
                     &mut length,
                     &mut size,
                     &mut var_type,
-                    name.as_ptr() as *mut gl::types::GLchar,
+                    name.as_mut_ptr(),
                 );
 
                 if length == 0 {
@@ -240,7 +391,7 @@ impl Shader {
 
                 self.uniforms.insert(
                     String::from_str(CStr::from_ptr(name.as_ptr()).to_str().unwrap()).unwrap(),
-                    location,
+                    (location, var_type),
                 );
             }
         }
@@ -253,7 +404,7 @@ impl Drop for Shader {
             gl::DeleteProgram(self.program);
         }
 
-        println!("Destroyed shader {}", self.name);
+        log::debug!("Destroyed shader {}", self.name);
     }
 }
 
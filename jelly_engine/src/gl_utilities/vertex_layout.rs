@@ -0,0 +1,210 @@
+use super::shader::Shader;
+use crate::error::Result;
+use crate::graphics::prelude::Color;
+use crate::math::prelude::{Vec2, Vec3};
+
+/// Data type of each component of an attribute
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AttributeType {
+    Float,
+    Byte,
+    UnsignedByte,
+    Short,
+    UnsignedShort,
+    Int,
+    UnsignedInt,
+}
+
+impl AttributeType {
+    pub fn size(self) -> usize {
+        match self {
+            AttributeType::Float => std::mem::size_of::<f32>(),
+            AttributeType::Byte | AttributeType::UnsignedByte => 1,
+            AttributeType::Short | AttributeType::UnsignedShort => 2,
+            AttributeType::Int | AttributeType::UnsignedInt => 4,
+        }
+    }
+
+    pub(crate) fn gl_enum(self) -> gl::types::GLenum {
+        match self {
+            AttributeType::Float => gl::FLOAT,
+            AttributeType::Byte => gl::BYTE,
+            AttributeType::UnsignedByte => gl::UNSIGNED_BYTE,
+            AttributeType::Short => gl::SHORT,
+            AttributeType::UnsignedShort => gl::UNSIGNED_SHORT,
+            AttributeType::Int => gl::INT,
+            AttributeType::UnsignedInt => gl::UNSIGNED_INT,
+        }
+    }
+}
+
+/// One attribute of a vertex, read by the shader input called `name`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VertexAttribute {
+    pub name: String,
+    pub kind: AttributeType,
+    pub count: gl::types::GLint,
+    /// Integer components are mapped to 0..1 (-1..1 when signed) floats
+    pub normalized: bool,
+    /// Integer components reach the shader as integers (`int`, `ivec`...)
+    pub integer: bool,
+    /// Byte offset in the vertex
+    pub offset: usize,
+}
+
+impl VertexAttribute {
+    /// Attribute described by the component type `C`
+    pub fn of<C: VertexComponent>(name: &str, offset: usize) -> VertexAttribute {
+        VertexAttribute {
+            name: String::from(name),
+            kind: C::KIND,
+            count: C::COUNT,
+            normalized: C::NORMALIZED,
+            integer: C::INTEGER,
+            offset,
+        }
+    }
+}
+
+/// Memory layout of a vertex type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VertexLayout {
+    /// Bytes between two consecutive vertices
+    pub stride: usize,
+    pub attributes: Vec<VertexAttribute>,
+}
+
+impl VertexLayout {
+    pub fn new(stride: usize) -> VertexLayout {
+        VertexLayout {
+            stride,
+            attributes: Vec::new(),
+        }
+    }
+
+    pub fn with_attribute(mut self, attribute: VertexAttribute) -> VertexLayout {
+        self.attributes.push(attribute);
+        self
+    }
+
+    /// Add the attribute `name` described by the component type `C`, found at `offset`
+    pub fn with_component<C: VertexComponent>(self, name: &str, offset: usize) -> VertexLayout {
+        self.with_attribute(VertexAttribute::of::<C>(name, offset))
+    }
+
+    /// Location of every attribute in `shader`, in the layout order
+    pub fn locations(&self, shader: &Shader) -> Result<Vec<gl::types::GLuint>> {
+        self.attributes
+            .iter()
+            .map(|attribute| shader.get_attribute_location(&attribute.name))
+            .collect()
+    }
+}
+
+/// Type usable as a vertex attribute
+pub trait VertexComponent {
+    const KIND: AttributeType;
+    const COUNT: gl::types::GLint;
+    const NORMALIZED: bool = false;
+    const INTEGER: bool = false;
+}
+
+/// `#[repr(C)]` vertex type uploaded to a `GLbuffer` as is, see `vertex_layout!`
+pub trait VertexFormat: Copy {
+    fn layout() -> VertexLayout;
+}
+
+/// Describe the layout of a `#[repr(C)]` struct from its fields, each mapped
+/// to the shader attribute it feeds. Field types must implement `VertexComponent`.
+///
+/// ```ignore
+/// impl VertexFormat for Vertex {
+///     fn layout() -> VertexLayout {
+///         vertex_layout!(Vertex {
+///             position => "a_position",
+///             tex_coord => "a_tex_coord",
+///         })
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! vertex_layout {
+    ($vertex:ty { $($field:ident => $name:expr),* $(,)? }) => {
+        $crate::gl_utilities::prelude::VertexLayout::new(std::mem::size_of::<$vertex>())
+            $(.with_attribute($crate::gl_utilities::prelude::field_attribute(
+                $name,
+                std::mem::offset_of!($vertex, $field),
+                |vertex: &$vertex| &vertex.$field,
+            )))*
+    };
+}
+
+/// Used by `vertex_layout!` to infer the type of a field from an accessor
+#[doc(hidden)]
+pub fn field_attribute<V, C: VertexComponent>(
+    name: &str,
+    offset: usize,
+    _field: fn(&V) -> &C,
+) -> VertexAttribute {
+    VertexAttribute::of::<C>(name, offset)
+}
+
+impl VertexComponent for f32 {
+    const KIND: AttributeType = AttributeType::Float;
+    const COUNT: gl::types::GLint = 1;
+}
+
+impl VertexComponent for [f32; 2] {
+    const KIND: AttributeType = AttributeType::Float;
+    const COUNT: gl::types::GLint = 2;
+}
+
+impl VertexComponent for [f32; 3] {
+    const KIND: AttributeType = AttributeType::Float;
+    const COUNT: gl::types::GLint = 3;
+}
+
+impl VertexComponent for [f32; 4] {
+    const KIND: AttributeType = AttributeType::Float;
+    const COUNT: gl::types::GLint = 4;
+}
+
+impl VertexComponent for Vec2 {
+    const KIND: AttributeType = AttributeType::Float;
+    const COUNT: gl::types::GLint = 2;
+}
+
+impl VertexComponent for Vec3 {
+    const KIND: AttributeType = AttributeType::Float;
+    const COUNT: gl::types::GLint = 3;
+}
+
+impl VertexComponent for Color {
+    const KIND: AttributeType = AttributeType::Float;
+    const COUNT: gl::types::GLint = 4;
+}
+
+/// Packed RGBA8 color, read as a normalized `vec4`
+impl VertexComponent for [u8; 4] {
+    const KIND: AttributeType = AttributeType::UnsignedByte;
+    const COUNT: gl::types::GLint = 4;
+    const NORMALIZED: bool = true;
+}
+
+impl VertexComponent for i32 {
+    const KIND: AttributeType = AttributeType::Int;
+    const COUNT: gl::types::GLint = 1;
+    const INTEGER: bool = true;
+}
+
+impl VertexComponent for u32 {
+    const KIND: AttributeType = AttributeType::UnsignedInt;
+    const COUNT: gl::types::GLint = 1;
+    const INTEGER: bool = true;
+}
+
+impl VertexComponent for [i32; 2] {
+    const KIND: AttributeType = AttributeType::Int;
+    const COUNT: gl::types::GLint = 2;
+    const INTEGER: bool = true;
+}
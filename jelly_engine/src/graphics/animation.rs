@@ -0,0 +1,519 @@
+use std::collections::HashMap;
+
+use super::prelude::{Sprite, TextureRegion};
+use crate::error::{JellyError, Result};
+
+// Keeps zero length frames from stalling `Animator::update`
+const MIN_FRAME_DURATION: f32 = 0.001;
+
+/// How a clip goes through its frames
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PlayMode {
+    /// First to last, then start over
+    Loop,
+    /// First to last, then stay on the last frame
+    Once,
+    /// First to last and back, forever
+    PingPong,
+    /// Last to first, then start over
+    Reverse,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationFrame<F> {
+    pub frame: F,
+    /// Seconds the frame stays on screen
+    pub duration: f32,
+    /// Reported by the animator when the frame is reached
+    pub event: Option<String>,
+}
+
+/// Sequence of frames, atlas regions by default.
+///
+/// Any frame type can be animated, which lets clips be stepped on the CPU
+/// without a texture, e.g. with frame indices.
+#[derive(Debug, Clone)]
+pub struct AnimationClip<F = TextureRegion> {
+    pub frames: Vec<AnimationFrame<F>>,
+    pub mode: PlayMode,
+}
+
+impl<F> AnimationClip<F> {
+    pub fn new(mode: PlayMode) -> AnimationClip<F> {
+        AnimationClip {
+            frames: Vec::new(),
+            mode,
+        }
+    }
+
+    /// Clip showing every frame for `frame_duration` seconds
+    pub fn from_frames(frames: Vec<F>, frame_duration: f32, mode: PlayMode) -> AnimationClip<F> {
+        AnimationClip {
+            frames: frames
+                .into_iter()
+                .map(|frame| AnimationFrame {
+                    frame,
+                    duration: frame_duration,
+                    event: None,
+                })
+                .collect(),
+            mode,
+        }
+    }
+
+    pub fn with_frame(mut self, frame: F, duration: f32) -> AnimationClip<F> {
+        self.frames.push(AnimationFrame {
+            frame,
+            duration,
+            event: None,
+        });
+        self
+    }
+
+    /// Fire `event` whenever frame `index` is reached
+    pub fn with_event(mut self, index: usize, event: &str) -> AnimationClip<F> {
+        if let Some(frame) = self.frames.get_mut(index) {
+            frame.event = Some(String::from(event));
+        }
+        self
+    }
+
+    /// Length of one pass through the frames, in seconds
+    pub fn duration(&self) -> f32 {
+        self.frames.iter().map(|frame| frame.duration).sum()
+    }
+
+    fn first_index(&self) -> usize {
+        match self.mode {
+            PlayMode::Reverse => self.frames.len().saturating_sub(1),
+            _ => 0,
+        }
+    }
+}
+
+/// Frame event raised while an animator plays
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnimationEvent {
+    pub clip: String,
+    pub frame: usize,
+    pub name: String,
+}
+
+type EventCallback = Box<dyn FnMut(&AnimationEvent)>;
+
+/// Plays named clips, advanced with the frame delta time
+pub struct Animator<F = TextureRegion> {
+    clips: HashMap<String, AnimationClip<F>>,
+    current: Option<String>,
+
+    frame: usize,
+    frame_time: f32,
+    // 1 or -1, only ping-pong goes backward and forward
+    direction: isize,
+
+    speed: f32,
+    playing: bool,
+    finished: bool,
+    // Reported as a frame change by the next update
+    restarted: bool,
+
+    events: Vec<AnimationEvent>,
+    callback: Option<EventCallback>,
+}
+
+impl<F> Animator<F> {
+    pub fn new() -> Animator<F> {
+        Animator {
+            clips: HashMap::new(),
+            current: None,
+            frame: 0,
+            frame_time: 0.0,
+            direction: 1,
+            speed: 1.0,
+            playing: false,
+            finished: false,
+            restarted: false,
+            events: Vec::new(),
+            callback: None,
+        }
+    }
+
+    pub fn add_clip(&mut self, name: &str, clip: AnimationClip<F>) {
+        self.clips.insert(String::from(name), clip);
+    }
+
+    /// Switch to the clip `name`, keeps going if it's already playing
+    pub fn play(&mut self, name: &str) -> Result<()> {
+        if self.current.as_deref() == Some(name) && self.playing {
+            return Ok(());
+        }
+
+        if !self.clips.contains_key(name) {
+            return Err(JellyError::AnimationNotFound(String::from(name)));
+        }
+
+        self.current = Some(String::from(name));
+        self.restart();
+
+        Ok(())
+    }
+
+    /// Play the current clip from its first frame
+    pub fn restart(&mut self) {
+        let first = match self.clip() {
+            Some(clip) if !clip.frames.is_empty() => clip.first_index(),
+            _ => return,
+        };
+
+        self.frame = first;
+        self.frame_time = 0.0;
+        self.direction = 1;
+        self.playing = true;
+        self.finished = false;
+        self.restarted = true;
+
+        self.enter_frame(first);
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn resume(&mut self) {
+        if !self.finished {
+            self.playing = true;
+        }
+    }
+
+    /// Playback rate, 2.0 plays twice as fast. Negative values are treated as 0
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.0);
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Called with every frame event as it happens, instead of queuing them
+    pub fn on_event<C: FnMut(&AnimationEvent) + 'static>(&mut self, callback: C) {
+        self.callback = Some(Box::new(callback));
+    }
+
+    /// Advance the animation, returns true when the displayed frame changed
+    /// since the last update (including a clip just started with `play`)
+    pub fn update(&mut self, dt: f32) -> bool {
+        let restarted = std::mem::take(&mut self.restarted);
+        if !self.playing {
+            return restarted;
+        }
+
+        let (mode, len) = match self.clip() {
+            Some(clip) if !clip.frames.is_empty() => (clip.mode, clip.frames.len()),
+            _ => return restarted,
+        };
+
+        let start_frame = self.frame;
+        self.frame_time += dt * self.speed;
+
+        loop {
+            let duration = self.frame_duration(self.frame);
+            if self.frame_time < duration {
+                break;
+            }
+
+            let next = match self.next_frame(mode, len) {
+                Some(next) => next,
+                None => {
+                    self.playing = false;
+                    self.finished = true;
+                    self.frame_time = 0.0;
+                    break;
+                }
+            };
+
+            self.frame_time -= duration;
+            self.frame = next;
+            self.enter_frame(next);
+        }
+
+        restarted || self.frame != start_frame
+    }
+
+    pub fn current_clip(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    pub fn frame_index(&self) -> usize {
+        self.frame
+    }
+
+    pub fn current_frame(&self) -> Option<&F> {
+        self.clip()
+            .and_then(|clip| clip.frames.get(self.frame))
+            .map(|frame| &frame.frame)
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// True once a `PlayMode::Once` clip reached the end of its last frame
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Events raised since the last call, empty when a callback is set
+    pub fn drain_events(&mut self) -> Vec<AnimationEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn clip(&self) -> Option<&AnimationClip<F>> {
+        self.current.as_ref().and_then(|name| self.clips.get(name))
+    }
+
+    fn frame_duration(&self, index: usize) -> f32 {
+        self.clip()
+            .and_then(|clip| clip.frames.get(index))
+            .map_or(MIN_FRAME_DURATION, |frame| {
+                frame.duration.max(MIN_FRAME_DURATION)
+            })
+    }
+
+    /// Frame after the current one, `None` when a `Once` clip is over
+    fn next_frame(&mut self, mode: PlayMode, len: usize) -> Option<usize> {
+        match mode {
+            PlayMode::Loop => Some((self.frame + 1) % len),
+            PlayMode::Reverse => Some(if self.frame == 0 {
+                len - 1
+            } else {
+                self.frame - 1
+            }),
+            PlayMode::Once => {
+                if self.frame + 1 < len {
+                    Some(self.frame + 1)
+                } else {
+                    None
+                }
+            }
+            PlayMode::PingPong => {
+                if len == 1 {
+                    return Some(0);
+                }
+
+                let next = self.frame as isize + self.direction;
+                if next < 0 || next >= len as isize {
+                    self.direction = -self.direction;
+                }
+
+                Some((self.frame as isize + self.direction) as usize)
+            }
+        }
+    }
+
+    fn enter_frame(&mut self, index: usize) {
+        let (clip_name, clip) = match &self.current {
+            Some(name) => match self.clips.get(name) {
+                Some(clip) => (name, clip),
+                None => return,
+            },
+            None => return,
+        };
+
+        if let Some(name) = clip.frames.get(index).and_then(|f| f.event.as_ref()) {
+            let event = AnimationEvent {
+                clip: clip_name.clone(),
+                frame: index,
+                name: name.clone(),
+            };
+
+            match &mut self.callback {
+                Some(callback) => callback(&event),
+                None => self.events.push(event),
+            }
+        }
+    }
+}
+
+impl Animator<TextureRegion> {
+    /// Advance the animation and show the current frame on `sprite`
+    pub fn animate(&mut self, sprite: &mut Sprite, dt: f32) {
+        if self.update(dt) {
+            if let Some(region) = self.current_frame() {
+                sprite.set_region(region);
+            }
+        }
+    }
+}
+
+impl<F> Default for Animator<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{cell::RefCell, rc::Rc};
+
+    // Frames 0 to 3, a quarter of a second each
+    fn animator(mode: PlayMode) -> Animator<usize> {
+        let mut animator = Animator::new();
+        animator.add_clip(
+            "walk",
+            AnimationClip::from_frames(vec![0, 1, 2, 3], 0.25, mode),
+        );
+        animator.play("walk").unwrap();
+
+        animator
+    }
+
+    // Frame shown after each step of `dt`
+    fn frames(animator: &mut Animator<usize>, dt: f32, steps: usize) -> Vec<usize> {
+        (0..steps)
+            .map(|_| {
+                animator.update(dt);
+                *animator.current_frame().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn loop_mode() {
+        let mut animator = animator(PlayMode::Loop);
+
+        assert_eq!(animator.current_frame(), Some(&0));
+        assert_eq!(frames(&mut animator, 0.25, 6), [1, 2, 3, 0, 1, 2]);
+        assert!(animator.is_playing());
+    }
+
+    #[test]
+    fn once_mode() {
+        let mut animator = animator(PlayMode::Once);
+
+        assert_eq!(frames(&mut animator, 0.25, 3), [1, 2, 3]);
+        assert!(!animator.is_finished());
+
+        assert_eq!(frames(&mut animator, 0.25, 2), [3, 3]);
+        assert!(animator.is_finished());
+        assert!(!animator.is_playing());
+
+        animator.resume();
+        assert!(!animator.is_playing());
+        animator.restart();
+        assert_eq!(animator.current_frame(), Some(&0));
+    }
+
+    #[test]
+    fn ping_pong_mode() {
+        let mut animator = animator(PlayMode::PingPong);
+
+        assert_eq!(frames(&mut animator, 0.25, 8), [1, 2, 3, 2, 1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn reverse_mode() {
+        let mut animator = animator(PlayMode::Reverse);
+
+        assert_eq!(animator.current_frame(), Some(&3));
+        assert_eq!(frames(&mut animator, 0.25, 5), [2, 1, 0, 3, 2]);
+    }
+
+    #[test]
+    fn several_frames_in_one_update() {
+        let mut animator = animator(PlayMode::Loop);
+
+        // Starting the clip counts as a frame change
+        assert!(animator.update(0.125));
+        assert!(!animator.update(0.0625));
+        assert!(animator.update(0.625));
+        assert_eq!(animator.frame_index(), 3);
+    }
+
+    #[test]
+    fn speed_scales_time() {
+        let mut animator = animator(PlayMode::Loop);
+
+        animator.set_speed(2.0);
+        assert_eq!(frames(&mut animator, 0.125, 3), [1, 2, 3]);
+
+        animator.set_speed(0.5);
+        assert_eq!(frames(&mut animator, 0.25, 4), [3, 0, 0, 1]);
+
+        animator.set_speed(-1.0);
+        assert_eq!(animator.speed(), 0.0);
+        assert_eq!(frames(&mut animator, 1.0, 2), [1, 1]);
+    }
+
+    #[test]
+    fn pause_and_resume() {
+        let mut animator = animator(PlayMode::Loop);
+
+        animator.pause();
+        assert_eq!(frames(&mut animator, 0.25, 2), [0, 0]);
+
+        animator.resume();
+        assert_eq!(frames(&mut animator, 0.25, 1), [1]);
+    }
+
+    #[test]
+    fn play_keeps_the_current_clip_going() {
+        let mut animator = animator(PlayMode::Loop);
+        animator.add_clip(
+            "idle",
+            AnimationClip::from_frames(vec![7], 1.0, PlayMode::Loop),
+        );
+
+        animator.update(0.25);
+        animator.play("walk").unwrap();
+        assert_eq!(animator.current_frame(), Some(&1));
+
+        animator.play("idle").unwrap();
+        assert_eq!(animator.current_clip(), Some("idle"));
+        assert_eq!(animator.current_frame(), Some(&7));
+        // The switch counts as a frame change
+        assert!(animator.update(0.0));
+
+        assert!(matches!(
+            animator.play("run"),
+            Err(JellyError::AnimationNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn frame_events() {
+        let mut animator = Animator::new();
+        animator.add_clip(
+            "attack",
+            AnimationClip::from_frames(vec![0, 1, 2], 0.25, PlayMode::Loop)
+                .with_event(0, "swing")
+                .with_event(2, "hit"),
+        );
+        animator.play("attack").unwrap();
+
+        let event = |frame: usize, name: &str| AnimationEvent {
+            clip: String::from("attack"),
+            frame,
+            name: String::from(name),
+        };
+
+        assert_eq!(animator.drain_events(), [event(0, "swing")]);
+
+        // Frames skipped within one update still raise their events
+        animator.update(0.75);
+        assert_eq!(
+            animator.drain_events(),
+            [event(2, "hit"), event(0, "swing")]
+        );
+        assert!(animator.drain_events().is_empty());
+
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let sink = received.clone();
+        animator.on_event(move |event| sink.borrow_mut().push(event.name.clone()));
+
+        animator.update(0.5);
+        assert_eq!(*received.borrow(), ["hit"]);
+        assert!(animator.drain_events().is_empty());
+    }
+}
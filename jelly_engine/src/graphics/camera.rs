@@ -0,0 +1,357 @@
+use std::rc::Rc;
+
+use super::prelude::{Color, RenderTarget, Viewport};
+use crate::math::prelude::{Matrix4x4, Vec2, Vec3};
+
+const NEAR_CLIP: f32 = -100.0;
+const FAR_CLIP: f32 = 100.0;
+
+/// Part of the screen, or of a render target, in 0..1 coordinates from the top left corner
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ViewRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ViewRect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> ViewRect {
+        ViewRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn full() -> ViewRect {
+        ViewRect::new(0.0, 0.0, 1.0, 1.0)
+    }
+
+    /// The rectangle inside `area`, in pixels. Neighbouring rectangles share
+    /// their edges, so split screens have no gap
+    pub fn to_pixels(&self, area: &Viewport) -> Viewport {
+        let left = (self.x * area.width as f32).round() as i32;
+        let right = ((self.x + self.width) * area.width as f32).round() as i32;
+        let top = (self.y * area.height as f32).round() as i32;
+        let bottom = ((self.y + self.height) * area.height as f32).round() as i32;
+
+        // GL counts rows from the bottom
+        Viewport::new(
+            area.x + left,
+            area.y + area.height - bottom,
+            right - left,
+            bottom - top,
+        )
+    }
+
+    /// Point of a `screen_size` screen as seen by a camera drawing into this
+    /// rectangle, scaled to `view_size`. `None` outside of the rectangle
+    pub fn to_local(&self, point: Vec2, screen_size: Vec2, view_size: Vec2) -> Option<Vec2> {
+        let x = (point.x / screen_size.x - self.x) / self.width;
+        let y = (point.y / screen_size.y - self.y) / self.height;
+
+        if (0.0..=1.0).contains(&x) && (0.0..=1.0).contains(&y) {
+            Some(Vec2::new(x * view_size.x, y * view_size.y))
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for ViewRect {
+    fn default() -> Self {
+        ViewRect::full()
+    }
+}
+
+/// Set of the 32 layers a camera sees, quads go on layer 0 by default
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LayerMask(pub u32);
+
+impl LayerMask {
+    pub const ALL: LayerMask = LayerMask(u32::MAX);
+    pub const NONE: LayerMask = LayerMask(0);
+
+    pub fn only(layer: u8) -> LayerMask {
+        LayerMask::NONE.with(layer)
+    }
+
+    pub fn with(self, layer: u8) -> LayerMask {
+        LayerMask(self.0 | layer_bit(layer))
+    }
+
+    pub fn without(self, layer: u8) -> LayerMask {
+        LayerMask(self.0 & !layer_bit(layer))
+    }
+
+    pub fn contains(self, layer: u8) -> bool {
+        self.0 & layer_bit(layer) != 0
+    }
+}
+
+impl Default for LayerMask {
+    fn default() -> Self {
+        LayerMask::ALL
+    }
+}
+
+// Layers past 31 are never seen
+fn layer_bit(layer: u8) -> u32 {
+    1u32.checked_shl(layer as u32).unwrap_or(0)
+}
+
+/// 2D camera looking at the world through the virtual resolution.
+///
+/// `position` is the world point shown at the center of the screen. Screen
+/// coordinates are virtual resolution coordinates, y pointing down, as
+/// returned by `Input::mouse_virtual_position`. A camera drawing into part of
+/// the screen gets its own screen coordinates, see `ViewRect::to_local`.
+#[derive(Debug, Clone)]
+pub struct Camera2D {
+    pub position: Vec2,
+    /// 2.0 shows everything twice as big
+    pub zoom: f32,
+    /// Radians
+    pub rotation: f32,
+
+    /// Size of the rectangle around the center the followed target moves in
+    /// freely, in world units
+    pub deadzone: Vec2,
+    /// How fast `follow` catches up with the target, 0 snaps to it
+    pub follow_speed: f32,
+
+    /// Largest shake displacement at full trauma, in world units
+    pub shake_offset: f32,
+    /// Largest shake rotation at full trauma, in radians
+    pub shake_angle: f32,
+    /// Trauma lost every second
+    pub trauma_decay: f32,
+    /// Speed of the shake noise
+    pub shake_frequency: f32,
+
+    /// Skipped when rendering while false
+    pub enabled: bool,
+    /// Where the camera draws, in its target or in the virtual screen
+    pub viewport: ViewRect,
+    /// Render target drawn into instead of the screen, e.g. for a minimap
+    pub target: Option<Rc<RenderTarget>>,
+    /// Color filling the viewport before drawing, nothing is cleared when `None`
+    pub clear_color: Option<Color>,
+    /// Layers of the quads the camera draws
+    pub layers: LayerMask,
+    /// Cameras draw from the lowest order to the highest, the main camera has order 0
+    pub order: i32,
+
+    size: Vec2,
+    bounds: Option<(Vec2, Vec2)>,
+    trauma: f32,
+    shake_time: f32,
+}
+
+impl Camera2D {
+    /// Camera showing exactly `width`x`height` world units, starting at the origin
+    pub fn new(width: u32, height: u32) -> Camera2D {
+        let size = Vec2::new(width as f32, height as f32);
+
+        Camera2D {
+            position: size.scale(0.5),
+            zoom: 1.0,
+            rotation: 0.0,
+            deadzone: Vec2::zero(),
+            follow_speed: 0.0,
+            shake_offset: 8.0,
+            shake_angle: 0.05,
+            trauma_decay: 1.0,
+            shake_frequency: 25.0,
+            enabled: true,
+            viewport: ViewRect::full(),
+            target: None,
+            clear_color: None,
+            layers: LayerMask::ALL,
+            order: 0,
+            size,
+            bounds: None,
+            trauma: 0.0,
+            shake_time: 0.0,
+        }
+    }
+
+    /// Size of the screen, in virtual pixels
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.size = Vec2::new(width as f32, height as f32);
+        self.clamp_to_bounds();
+    }
+
+    /// Keep the visible area inside the world rectangle `min`..`max`
+    pub fn set_bounds(&mut self, min: Vec2, max: Vec2) {
+        self.bounds = Some((min, max));
+        self.clamp_to_bounds();
+    }
+
+    pub fn clear_bounds(&mut self) {
+        self.bounds = None;
+    }
+
+    pub fn bounds(&self) -> Option<(Vec2, Vec2)> {
+        self.bounds
+    }
+
+    pub fn look_at(&mut self, target: Vec2) {
+        self.position = target;
+        self.clamp_to_bounds();
+    }
+
+    /// Move toward `target` once it leaves the deadzone
+    pub fn follow(&mut self, target: Vec2, dt: f32) {
+        let half_deadzone = self.deadzone.scale(0.5);
+        let mut goal = self.position;
+
+        for (goal, target, half) in [
+            (&mut goal.x, target.x, half_deadzone.x),
+            (&mut goal.y, target.y, half_deadzone.y),
+        ] {
+            if target < *goal - half {
+                *goal = target + half;
+            } else if target > *goal + half {
+                *goal = target - half;
+            }
+        }
+
+        let t = if self.follow_speed > 0.0 {
+            1.0 - (-self.follow_speed * dt).exp()
+        } else {
+            1.0
+        };
+
+        self.position = self.position + (goal - self.position).scale(t);
+        self.clamp_to_bounds();
+    }
+
+    /// Shake harder, trauma is kept between 0 and 1 and the shake grows
+    /// with its square
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    /// Advance the shake, called by the engine after every fixed update
+    pub fn update(&mut self, dt: f32) {
+        if self.trauma > 0.0 {
+            self.trauma = (self.trauma - self.trauma_decay * dt).max(0.0);
+            self.shake_time += dt;
+        }
+    }
+
+    pub fn projection(&self) -> Matrix4x4 {
+        Matrix4x4::orthographic(0.0, self.size.x, 0.0, self.size.y, NEAR_CLIP, FAR_CLIP)
+    }
+
+    /// World to screen transform, shake included
+    pub fn view(&self) -> Matrix4x4 {
+        let (position, rotation) = self.shaken();
+        let half_size = self.size.scale(0.5);
+
+        Matrix4x4::translation(Vec3::new(half_size.x, half_size.y, 0.0))
+            * Matrix4x4::scale(Vec3::new(self.zoom, self.zoom, 1.0))
+            * Matrix4x4::rotation_z(-rotation)
+            * Matrix4x4::translation(Vec3::new(-position.x, -position.y, 0.0))
+    }
+
+    /// Matrix handed to the shaders as `u_projection`
+    pub fn view_projection(&self) -> Matrix4x4 {
+        self.projection() * self.view()
+    }
+
+    /// World point under the screen point `screen`
+    pub fn screen_to_world(&self, screen: Vec2) -> Vec2 {
+        let (position, rotation) = self.shaken();
+        let centered = (screen - self.size.scale(0.5)).scale(1.0 / self.zoom);
+
+        position + rotate(centered, rotation)
+    }
+
+    /// Screen point showing the world point `world`
+    pub fn world_to_screen(&self, world: Vec2) -> Vec2 {
+        let (position, rotation) = self.shaken();
+        let centered = rotate(world - position, -rotation).scale(self.zoom);
+
+        centered + self.size.scale(0.5)
+    }
+
+    /// Axis aligned world rectangle covering the screen, as (min, max)
+    pub fn visible_area(&self) -> (Vec2, Vec2) {
+        let corners = [
+            Vec2::zero(),
+            Vec2::new(self.size.x, 0.0),
+            Vec2::new(0.0, self.size.y),
+            self.size,
+        ];
+
+        let mut min = Vec2::new(f32::MAX, f32::MAX);
+        let mut max = Vec2::new(f32::MIN, f32::MIN);
+        for corner in corners.iter() {
+            let world = self.screen_to_world(*corner);
+            min = Vec2::new(min.x.min(world.x), min.y.min(world.y));
+            max = Vec2::new(max.x.max(world.x), max.y.max(world.y));
+        }
+
+        (min, max)
+    }
+
+    fn clamp_to_bounds(&mut self) {
+        let (min, max) = match self.bounds {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        let half_view = self.size.scale(0.5 / self.zoom);
+
+        for (position, min, max, half) in [
+            (&mut self.position.x, min.x, max.x, half_view.x),
+            (&mut self.position.y, min.y, max.y, half_view.y),
+        ] {
+            *position = if max - min <= half * 2.0 {
+                // Bounds smaller than the view stay centered
+                (min + max) / 2.0
+            } else {
+                position.clamp(min + half, max - half)
+            };
+        }
+    }
+
+    /// Position and rotation with the shake applied
+    fn shaken(&self) -> (Vec2, f32) {
+        let shake = self.trauma * self.trauma;
+        if shake <= 0.0 {
+            return (self.position, self.rotation);
+        }
+
+        let t = self.shake_time * self.shake_frequency;
+        let offset = Vec2::new(noise(t, 0.0), noise(t, 17.0)).scale(self.shake_offset * shake);
+
+        (
+            self.position + offset,
+            self.rotation + noise(t, 31.0) * self.shake_angle * shake,
+        )
+    }
+}
+
+fn rotate(v: Vec2, angle: f32) -> Vec2 {
+    let (s, c) = angle.sin_cos();
+
+    Vec2::new(v.x * c - v.y * s, v.x * s + v.y * c)
+}
+
+/// Smooth pseudo random value in -1..1, a different curve for every `seed`
+fn noise(t: f32, seed: f32) -> f32 {
+    (t + seed).sin() * 0.5 + (t * 2.3 + seed * 1.7).sin() * 0.3 + (t * 5.9 + seed * 2.9).sin() * 0.2
+}
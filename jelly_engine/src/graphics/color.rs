@@ -1,4 +1,7 @@
 use std::collections::HashMap;
+
+use crate::error::{JellyError, Result};
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 /// Error message type when failing to convert a hex code to RGB.
 pub enum HtmlColorConversionError {
@@ -8,6 +11,7 @@ pub enum HtmlColorConversionError {
 }
 
 #[derive(PartialEq, Copy, Clone, Default, Debug)]
+#[repr(C)]
 pub struct Color {
     pub r: f32,
     pub g: f32,
@@ -34,7 +38,7 @@ impl Color {
         }
     }
 
-    pub fn from_hex<S: AsRef<str>>(hash: S) -> Result<Self, HtmlColorConversionError> {
+    pub fn from_hex<S: AsRef<str>>(hash: S) -> std::result::Result<Self, HtmlColorConversionError> {
         let mut code = hash.as_ref().chars();
 
         if let Some(hash) = code.next() {
@@ -96,7 +100,7 @@ impl Color {
         Ok(color)
     }
 
-    pub fn from_palette(c: &str) -> Result<Self, String> {
+    pub fn from_palette(c: &str) -> Result<Self> {
         let mut palette = HashMap::new();
         palette.insert("black", "#000000");
         palette.insert("dark_blue", "#1D2B53");
@@ -120,13 +124,13 @@ impl Color {
 
         for (k, _) in palette.iter() {
             if &c == k {
-                color = Some(Self::from_hex(palette.get(&c).unwrap()).unwrap());
+                color = Some(Self::from_hex(palette.get(&c).unwrap())?);
             }
         }
 
         match color {
             Some(color) => Ok(color),
-            None => Err(String::from("Invalid color")),
+            None => Err(JellyError::InvalidColor(String::from(c))),
         }
     }
 
@@ -140,4 +144,11 @@ impl Color {
     pub fn as_tuple(&self) -> (f32, f32, f32, f32) {
         (self.r, self.g, self.b, self.a)
     }
+
+    /// Components packed as bytes, e.g. for vertex colors
+    pub fn to_rgba8(&self) -> [u8; 4] {
+        let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        [byte(self.r), byte(self.g), byte(self.b), byte(self.a)]
+    }
 }
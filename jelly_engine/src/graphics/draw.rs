@@ -0,0 +1,209 @@
+use super::prelude::{Color, Quad, ShapeRenderer, SpriteBatch, Texture, TextureRegion};
+use crate::math::prelude::{Matrix4x4, Vec2, Vec3};
+use crate::text::prelude::{Font, TextLayout};
+
+/// Immediate drawing for quick prototypes, in the spirit of `love.graphics`.
+///
+/// Everything is queued in the `SpriteBatch` with the current color, through
+/// a transform built with `translate`, `rotate` and `scale`, and saved and
+/// restored with `push` and `pop`. Get one with `Context::draw` in
+/// `Game::render`, the color and the transform start over every time.
+pub struct Draw<'a> {
+    batch: &'a mut SpriteBatch,
+    shapes: ShapeRenderer,
+    color: Color,
+    line_width: f32,
+    transform: Matrix4x4,
+    stack: Vec<Matrix4x4>,
+    time: f32,
+}
+
+impl<'a> Draw<'a> {
+    /// `shapes` gives the segments and joins of shapes, `time` in seconds
+    /// drives the effects of text layouts
+    pub fn new(batch: &'a mut SpriteBatch, shapes: &ShapeRenderer, time: f32) -> Draw<'a> {
+        Draw {
+            batch,
+            shapes: shapes.clone(),
+            color: Color::white(),
+            line_width: 1.0,
+            transform: Matrix4x4::identity(),
+            stack: Vec::new(),
+            time,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    /// Tint of textures and text, and color of shapes
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+
+    pub fn line_width(&self) -> f32 {
+        self.line_width
+    }
+
+    /// Thickness of lines and outlines
+    pub fn set_line_width(&mut self, line_width: f32) {
+        self.line_width = line_width;
+    }
+
+    pub fn transform(&self) -> Matrix4x4 {
+        self.transform
+    }
+
+    /// Save the current transform, until the matching `pop`
+    pub fn push(&mut self) {
+        self.stack.push(self.transform);
+    }
+
+    /// Restore the transform saved by the last `push`
+    pub fn pop(&mut self) {
+        match self.stack.pop() {
+            Some(transform) => self.transform = transform,
+            None => log::warn!("Draw::pop without a matching push"),
+        }
+    }
+
+    /// Back to the identity transform, the stack is kept
+    pub fn origin(&mut self) {
+        self.transform = Matrix4x4::identity();
+    }
+
+    /// Move the origin of the next draws, in the current space
+    pub fn translate(&mut self, x: f32, y: f32) {
+        self.apply_transform(&Matrix4x4::translation(Vec3::new(x, y, 0.0)));
+    }
+
+    /// Turn the next draws around the origin, in radians, clockwise on screen
+    pub fn rotate(&mut self, angle: f32) {
+        self.apply_transform(&Matrix4x4::rotation_z(angle));
+    }
+
+    pub fn scale(&mut self, x: f32, y: f32) {
+        self.apply_transform(&Matrix4x4::scale(Vec3::new(x, y, 1.0)));
+    }
+
+    /// Combine `transform` with the current one, it applies first
+    pub fn apply_transform(&mut self, transform: &Matrix4x4) {
+        self.transform *= transform;
+    }
+
+    /// The whole texture, with its top left corner at (x, y)
+    pub fn texture(&mut self, texture: &Texture, x: f32, y: f32) {
+        let quad = Quad::new(texture.width() as f32, texture.height() as f32);
+        self.quad(texture, quad, x, y);
+    }
+
+    /// Part of a texture, with its top left corner at (x, y)
+    pub fn region(&mut self, region: &TextureRegion, x: f32, y: f32) {
+        self.quad(&region.texture, Quad::from_region(region), x, y);
+    }
+
+    /// `quad` with its origin at (x, y), its tint multiplied by the color
+    pub fn quad(&mut self, texture: &Texture, mut quad: Quad, x: f32, y: f32) {
+        quad.tint = multiply(quad.tint, self.color);
+        let model = self.model(x, y);
+        self.batch.draw(texture, &quad, &model);
+    }
+
+    /// `text` with its top left corner at (x, y). For a `TrueTypeFont`, pass
+    /// `font.font(size, text)`
+    pub fn text(&mut self, font: &Font, text: &str, x: f32, y: f32) {
+        let model = self.model(x, y);
+        font.draw(self.batch, text, &model, self.color);
+    }
+
+    /// `layout` with the top left corner of its box at (x, y)
+    pub fn layout(&mut self, font: &Font, layout: &TextLayout, x: f32, y: f32) {
+        let model = self.model(x, y);
+        layout.draw(font, self.batch, &model, self.color, self.time);
+    }
+
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.prepare_shapes();
+        self.shapes.fill_rect(
+            self.batch,
+            Vec2::new(x, y),
+            Vec2::new(width, height),
+            self.color,
+        );
+    }
+
+    pub fn stroke_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.prepare_shapes();
+        self.shapes.stroke_rect(
+            self.batch,
+            Vec2::new(x, y),
+            Vec2::new(width, height),
+            self.line_width,
+            self.color,
+        );
+    }
+
+    pub fn fill_circle(&mut self, x: f32, y: f32, radius: f32) {
+        self.prepare_shapes();
+        self.shapes
+            .fill_circle(self.batch, Vec2::new(x, y), radius, self.color);
+    }
+
+    pub fn stroke_circle(&mut self, x: f32, y: f32, radius: f32) {
+        self.prepare_shapes();
+        self.shapes.stroke_circle(
+            self.batch,
+            Vec2::new(x, y),
+            radius,
+            self.line_width,
+            self.color,
+        );
+    }
+
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        self.prepare_shapes();
+        self.shapes.line(
+            self.batch,
+            Vec2::new(x1, y1),
+            Vec2::new(x2, y2),
+            self.line_width,
+            self.color,
+        );
+    }
+
+    pub fn polyline(&mut self, points: &[Vec2]) {
+        self.prepare_shapes();
+        self.shapes
+            .polyline(self.batch, points, self.line_width, self.color);
+    }
+
+    /// `points` must outline a convex polygon
+    pub fn fill_polygon(&mut self, points: &[Vec2]) {
+        self.prepare_shapes();
+        self.shapes.fill_polygon(self.batch, points, self.color);
+    }
+
+    pub fn stroke_polygon(&mut self, points: &[Vec2]) {
+        self.prepare_shapes();
+        self.shapes
+            .stroke_polygon(self.batch, points, self.line_width, self.color);
+    }
+
+    fn model(&self, x: f32, y: f32) -> Matrix4x4 {
+        self.transform * Matrix4x4::translation(Vec3::new(x, y, 0.0))
+    }
+
+    fn prepare_shapes(&mut self) {
+        self.shapes.transform = self.transform;
+    }
+}
+
+fn multiply(a: Color, b: Color) -> Color {
+    Color {
+        r: a.r * b.r,
+        g: a.g * b.g,
+        b: a.b * b.b,
+        a: a.a * b.a,
+    }
+}
@@ -1,13 +1,150 @@
 use super::prelude::{Color, Texture};
+use crate::error::{JellyError, Result};
+use crate::gl_utilities::prelude::{Shader, UniformValue};
 
-#[derive(Debug, Clone, Copy)]
-pub struct Material<'a> {
+/// How a sprite is drawn: a shader, with the values of its uniforms.
+///
+/// The tint goes to `u_tint` and the texture to `u_diffuse`, when the shader
+/// has them. Extra textures are bound from unit 1, and params can set any
+/// other uniform, e.g. the amount of a flash or a dissolve.
+#[derive(Debug, Clone)]
+pub struct Material {
+    shader: String,
     pub tint: Color,
-    pub texture: &'a Texture,
+    pub texture: Texture,
+    params: Vec<(String, UniformValue)>,
+    textures: Vec<(String, Texture)>,
 }
 
-impl<'a> Material<'a> {
-    pub fn new(tint: Color, texture: &'a Texture) -> Material {
-        Material { tint, texture }
+impl Material {
+    /// Shader of materials created with `new`
+    pub const DEFAULT_SHADER: &'static str = "basic";
+
+    pub fn new(tint: Color, texture: Texture) -> Material {
+        Material {
+            shader: String::from(Material::DEFAULT_SHADER),
+            tint,
+            texture,
+            params: Vec::new(),
+            textures: Vec::new(),
+        }
+    }
+
+    /// Draw with `shader`, registered in the `ShaderManager`
+    pub fn with_shader(mut self, shader: &str) -> Material {
+        self.shader = String::from(shader);
+        self
+    }
+
+    /// Name of the shader in the `ShaderManager`
+    pub fn shader(&self) -> &str {
+        &self.shader
+    }
+
+    pub fn with_param(mut self, name: &str, value: UniformValue) -> Material {
+        self.set_param(name, value);
+        self
+    }
+
+    /// Extra texture sampled by the shader through the uniform `name`
+    pub fn with_texture(mut self, name: &str, texture: Texture) -> Material {
+        self.set_texture(name, texture);
+        self
+    }
+
+    pub fn set_param(&mut self, name: &str, value: UniformValue) {
+        match self.params.iter_mut().find(|(param, _)| param == name) {
+            Some((_, current)) => *current = value,
+            None => self.params.push((String::from(name), value)),
+        }
+    }
+
+    pub fn param(&self, name: &str) -> Option<UniformValue> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| *value)
+    }
+
+    pub fn remove_param(&mut self, name: &str) -> Option<UniformValue> {
+        let index = self.params.iter().position(|(param, _)| param == name)?;
+
+        Some(self.params.remove(index).1)
+    }
+
+    pub fn set_texture(&mut self, name: &str, texture: Texture) {
+        match self
+            .textures
+            .iter_mut()
+            .find(|(uniform, _)| uniform == name)
+        {
+            Some((_, current)) => *current = texture,
+            None => self.textures.push((String::from(name), texture)),
+        }
+    }
+
+    /// Extra texture bound to the uniform `name`
+    pub fn texture_slot(&self, name: &str) -> Option<Texture> {
+        self.textures
+            .iter()
+            .find(|(uniform, _)| uniform == name)
+            .map(|(_, texture)| *texture)
+    }
+
+    /// Default shader without params nor extra textures, which the
+    /// `SpriteBatch` can draw with its own shader
+    pub fn is_plain(&self) -> bool {
+        self.shader == Material::DEFAULT_SHADER
+            && self.params.is_empty()
+            && self.textures.is_empty()
     }
+
+    /// Check every param and extra texture has a uniform of its type in `shader`
+    pub fn validate(&self, shader: &Shader) -> Result<()> {
+        for (name, value) in &self.params {
+            shader.check_uniform(name, value)?;
+        }
+        for (name, _) in &self.textures {
+            texture_location(shader, name)?;
+        }
+
+        Ok(())
+    }
+
+    /// Set the uniforms of `shader` and bind the textures, the shader must be in use
+    pub fn apply(&self, shader: &Shader) -> Result<()> {
+        if let Ok(location) = shader.get_uniform_location("u_tint") {
+            UniformValue::Color(self.tint).apply(location);
+        }
+        if let Ok(location) = shader.get_uniform_location("u_diffuse") {
+            self.texture.activate_unit(0);
+            UniformValue::Int(0).apply(location);
+        }
+
+        for (unit, (name, texture)) in self.textures.iter().enumerate() {
+            let unit = unit as u32 + 1;
+            texture.activate_unit(unit);
+            UniformValue::Int(unit as i32).apply(texture_location(shader, name)?);
+        }
+        for (name, value) in &self.params {
+            value.apply(shader.check_uniform(name, value)?);
+        }
+
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+
+        Ok(())
+    }
+}
+
+fn texture_location(shader: &Shader, name: &str) -> Result<gl::types::GLint> {
+    if shader.get_uniform_type(name)? != gl::SAMPLER_2D {
+        return Err(JellyError::UniformTypeMismatch {
+            shader: shader.name.clone(),
+            name: String::from(name),
+        });
+    }
+
+    shader.get_uniform_location(name)
 }
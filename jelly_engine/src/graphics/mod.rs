@@ -1,13 +1,31 @@
+mod animation;
+mod camera;
 mod color;
+mod draw;
 mod material;
+mod post_process;
+mod render_target;
+mod shapes;
 mod sprite;
+mod sprite_batch;
 mod texture;
+mod texture_atlas;
 mod vertex;
+mod viewport;
 
 pub mod prelude {
+    pub use crate::graphics::animation::*;
+    pub use crate::graphics::camera::*;
     pub use crate::graphics::color::*;
+    pub use crate::graphics::draw::*;
     pub use crate::graphics::material::*;
+    pub use crate::graphics::post_process::*;
+    pub use crate::graphics::render_target::*;
+    pub use crate::graphics::shapes::*;
     pub use crate::graphics::sprite::*;
+    pub use crate::graphics::sprite_batch::*;
     pub use crate::graphics::texture::*;
+    pub use crate::graphics::texture_atlas::*;
     pub use crate::graphics::vertex::*;
+    pub use crate::graphics::viewport::*;
 }
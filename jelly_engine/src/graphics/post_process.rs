@@ -0,0 +1,344 @@
+use super::prelude::{RenderTarget, Texture};
+use crate::error::Result;
+use crate::gl_utilities::prelude::{
+    BufferUsage, GLbuffer, Shader, ShaderManager, UniformValue, VertexFormat, VertexLayout,
+};
+use crate::math::prelude::Vec2;
+use crate::vertex_layout;
+
+/// Vertex shader shared by every effect, to register custom effects with.
+///
+/// It hands `v_tex_coord` to the fragment shader, which can read the previous
+/// pass from `u_texture`, its size in pixels from `u_resolution` and the
+/// elapsed time in seconds from `u_time`.
+pub const POST_VERTEX_SHADER: &str = include_str!("../post/post.vert");
+
+const BUILTIN_EFFECTS: [(&str, &str); 6] = [
+    ("post_crt", include_str!("../post/crt.frag")),
+    ("post_vignette", include_str!("../post/vignette.frag")),
+    (
+        "post_chromatic_aberration",
+        include_str!("../post/chromatic_aberration.frag"),
+    ),
+    ("post_bloom", include_str!("../post/bloom.frag")),
+    (
+        "post_color_grading",
+        include_str!("../post/color_grading.frag"),
+    ),
+    ("post_dither", include_str!("../post/dither.frag")),
+];
+
+/// A full screen pass of the post-processing chain
+#[derive(Debug, Clone)]
+pub struct PostEffect {
+    pub name: String,
+    /// Name of the shader in the `ShaderManager`
+    pub shader: String,
+    pub enabled: bool,
+    params: Vec<(String, UniformValue)>,
+    textures: Vec<(String, Texture)>,
+}
+
+impl PostEffect {
+    pub fn new(name: &str, shader: &str) -> PostEffect {
+        PostEffect {
+            name: String::from(name),
+            shader: String::from(shader),
+            enabled: true,
+            params: Vec::new(),
+            textures: Vec::new(),
+        }
+    }
+
+    pub fn with_param(mut self, name: &str, value: UniformValue) -> PostEffect {
+        self.set_param(name, value);
+        self
+    }
+
+    /// Extra texture sampled by the shader through the uniform `name`
+    pub fn with_texture(mut self, name: &str, texture: Texture) -> PostEffect {
+        self.textures.retain(|(uniform, _)| uniform != name);
+        self.textures.push((String::from(name), texture));
+        self
+    }
+
+    pub fn set_param(&mut self, name: &str, value: UniformValue) {
+        match self.params.iter_mut().find(|(param, _)| param == name) {
+            Some((_, current)) => *current = value,
+            None => self.params.push((String::from(name), value)),
+        }
+    }
+
+    pub fn param(&self, name: &str) -> Option<UniformValue> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| *value)
+    }
+
+    /// Screen curvature and scanlines
+    pub fn crt() -> PostEffect {
+        PostEffect::new("crt", "post_crt")
+            .with_param("u_curvature", UniformValue::Float(0.05))
+            .with_param("u_scanlines", UniformValue::Float(0.25))
+    }
+
+    /// Darkened corners, `u_radius` and `u_softness` in half screen diagonals
+    pub fn vignette() -> PostEffect {
+        PostEffect::new("vignette", "post_vignette")
+            .with_param("u_intensity", UniformValue::Float(0.5))
+            .with_param("u_radius", UniformValue::Float(0.9))
+            .with_param("u_softness", UniformValue::Float(0.5))
+    }
+
+    /// Red and blue channels drifting apart toward the edges, `u_offset` in pixels
+    pub fn chromatic_aberration() -> PostEffect {
+        PostEffect::new("chromatic_aberration", "post_chromatic_aberration")
+            .with_param("u_offset", UniformValue::Float(1.0))
+    }
+
+    /// Glow around the pixels brighter than `u_threshold`
+    pub fn bloom() -> PostEffect {
+        PostEffect::new("bloom", "post_bloom")
+            .with_param("u_threshold", UniformValue::Float(0.7))
+            .with_param("u_intensity", UniformValue::Float(0.6))
+            .with_param("u_radius", UniformValue::Float(2.0))
+    }
+
+    /// Remap colors through a 256x16 lookup table of 16 blue slices
+    pub fn color_grading(lut: Texture) -> PostEffect {
+        PostEffect::new("color_grading", "post_color_grading")
+            .with_texture("u_lut", lut)
+            .with_param("u_intensity", UniformValue::Float(1.0))
+    }
+
+    /// Ordered dithering down to `levels` shades per channel
+    pub fn dither(levels: u32) -> PostEffect {
+        PostEffect::new("dither", "post_dither")
+            .with_param("u_levels", UniformValue::Float(levels as f32))
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+struct ScreenVertex {
+    position: [f32; 2],
+    tex_coord: [f32; 2],
+}
+
+impl VertexFormat for ScreenVertex {
+    fn layout() -> VertexLayout {
+        vertex_layout!(ScreenVertex {
+            position => "a_position",
+            tex_coord => "a_tex_coord",
+        })
+    }
+}
+
+/// Chain of full screen effects applied to the canvas before it reaches the window.
+///
+/// Enabled effects run in order, each one reading the output of the previous
+/// one, ping-ponging between two targets the size of the canvas. The chain
+/// only runs when the engine renders to a canvas, see `Config::canvas`.
+#[derive(Default)]
+pub struct PostProcess {
+    effects: Vec<PostEffect>,
+    targets: Vec<RenderTarget>,
+    quad: Option<GLbuffer>,
+}
+
+impl PostProcess {
+    pub fn new() -> PostProcess {
+        PostProcess::default()
+    }
+
+    /// Register the shaders used by the built-in effects
+    pub fn register_builtins(shader_manager: &mut ShaderManager) -> Result<()> {
+        for (name, fragment_source) in BUILTIN_EFFECTS.iter() {
+            shader_manager.register(name, POST_VERTEX_SHADER, fragment_source)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn push(&mut self, effect: PostEffect) {
+        self.effects.push(effect);
+    }
+
+    pub fn insert(&mut self, index: usize, effect: PostEffect) {
+        self.effects.insert(index.min(self.effects.len()), effect);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<PostEffect> {
+        let index = self.effects.iter().position(|effect| effect.name == name)?;
+
+        Some(self.effects.remove(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+
+    pub fn effects(&self) -> &[PostEffect] {
+        &self.effects
+    }
+
+    pub fn effect(&self, name: &str) -> Option<&PostEffect> {
+        self.effects.iter().find(|effect| effect.name == name)
+    }
+
+    pub fn effect_mut(&mut self, name: &str) -> Option<&mut PostEffect> {
+        self.effects.iter_mut().find(|effect| effect.name == name)
+    }
+
+    /// Toggle the effect `name`, returns false when there is no such effect
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self.effect_mut(name) {
+            Some(effect) => {
+                effect.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// True when at least one effect is enabled
+    pub fn is_active(&self) -> bool {
+        self.effects.iter().any(|effect| effect.enabled)
+    }
+
+    /// Run the enabled effects over `source`, returns the target holding the
+    /// result or `None` when there was nothing to apply
+    pub fn apply(
+        &mut self,
+        shader_manager: &ShaderManager,
+        source: &RenderTarget,
+        time: f32,
+    ) -> Result<Option<&RenderTarget>> {
+        if !self.is_active() {
+            return Ok(None);
+        }
+
+        self.prepare(source.width(), source.height())?;
+
+        let mut previous_program = 0;
+        unsafe {
+            gl::GetIntegerv(gl::CURRENT_PROGRAM, &mut previous_program);
+            // Each pass replaces the whole target
+            gl::Disable(gl::BLEND);
+        }
+
+        let result = self.run_passes(shader_manager, source, time);
+
+        RenderTarget::unbind();
+        unsafe {
+            gl::Enable(gl::BLEND);
+            gl::UseProgram(previous_program as gl::types::GLuint);
+        }
+
+        Ok(Some(&self.targets[result?]))
+    }
+
+    /// Create the targets and the screen quad, or resize the targets
+    fn prepare(&mut self, width: u32, height: u32) -> Result<()> {
+        let resized = self
+            .targets
+            .first()
+            .is_some_and(|target| target.width() != width || target.height() != height);
+        if resized {
+            self.targets.clear();
+        }
+
+        while self.targets.len() < 2 {
+            self.targets.push(RenderTarget::new(width, height, false)?);
+        }
+
+        if self.quad.is_none() {
+            let mut quad = GLbuffer::with_usage(BufferUsage::Static);
+            // Locations are fixed in post.vert
+            quad.configure_for::<ScreenVertex>(&[0, 1]);
+            quad.upload(&[
+                ScreenVertex {
+                    position: [-1.0, -1.0],
+                    tex_coord: [0.0, 0.0],
+                },
+                ScreenVertex {
+                    position: [-1.0, 1.0],
+                    tex_coord: [0.0, 1.0],
+                },
+                ScreenVertex {
+                    position: [1.0, 1.0],
+                    tex_coord: [1.0, 1.0],
+                },
+                ScreenVertex {
+                    position: [1.0, -1.0],
+                    tex_coord: [1.0, 0.0],
+                },
+            ]);
+            quad.upload_indices(&[0, 1, 2, 2, 3, 0]);
+
+            self.quad = Some(quad);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the index of the target written last
+    fn run_passes(
+        &self,
+        shader_manager: &ShaderManager,
+        source: &RenderTarget,
+        time: f32,
+    ) -> Result<usize> {
+        let quad = match &self.quad {
+            Some(quad) => quad,
+            None => return Ok(0),
+        };
+
+        let resolution =
+            UniformValue::Vec2(Vec2::new(source.width() as f32, source.height() as f32));
+
+        let mut input = source.texture();
+        let mut output = 0;
+
+        for effect in self.effects.iter().filter(|effect| effect.enabled) {
+            let target = &self.targets[output];
+            target.bind();
+
+            let shader = shader_manager.get(&effect.shader)?;
+            shader.use_shader();
+
+            input.activate_unit(0);
+            set_if_used(shader, "u_texture", &UniformValue::Int(0));
+            set_if_used(shader, "u_resolution", &resolution);
+            set_if_used(shader, "u_time", &UniformValue::Float(time));
+
+            for (unit, (name, texture)) in effect.textures.iter().enumerate() {
+                let unit = unit as u32 + 1;
+                texture.activate_unit(unit);
+                set_if_used(shader, name, &UniformValue::Int(unit as i32));
+            }
+            for (name, value) in &effect.params {
+                set_if_used(shader, name, value);
+            }
+
+            quad.draw();
+
+            input = target.texture();
+            output = 1 - output;
+        }
+
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+
+        Ok(1 - output)
+    }
+}
+
+/// Uniforms the compiler optimized away are skipped
+fn set_if_used(shader: &Shader, name: &str, value: &UniformValue) {
+    if let Ok(location) = shader.get_uniform_location(name) {
+        value.apply(location);
+    }
+}
@@ -0,0 +1,138 @@
+use super::prelude::{Texture, Viewport};
+use crate::error::{JellyError, Result};
+
+/// Off-screen framebuffer drawn into a texture.
+///
+/// The color attachment is an RGBA8 texture sampled with nearest filtering,
+/// depth and stencil live in an optional renderbuffer.
+#[derive(Debug)]
+pub struct RenderTarget {
+    fbo: gl::types::GLuint,
+    // 0 without depth/stencil attachment
+    depth_stencil: gl::types::GLuint,
+    texture: Texture,
+}
+
+impl RenderTarget {
+    pub fn new(width: u32, height: u32, depth_stencil: bool) -> Result<RenderTarget> {
+        let texture = Texture::empty(width, height);
+
+        let mut target = RenderTarget {
+            fbo: 0,
+            depth_stencil: 0,
+            texture,
+        };
+
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, texture.id());
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+
+            gl::GenFramebuffers(1, &mut target.fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, target.fbo);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                texture.id(),
+                0,
+            );
+
+            if depth_stencil {
+                gl::GenRenderbuffers(1, &mut target.depth_stencil);
+                gl::BindRenderbuffer(gl::RENDERBUFFER, target.depth_stencil);
+                gl::RenderbufferStorage(
+                    gl::RENDERBUFFER,
+                    gl::DEPTH24_STENCIL8,
+                    width as i32,
+                    height as i32,
+                );
+                gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+
+                gl::FramebufferRenderbuffer(
+                    gl::FRAMEBUFFER,
+                    gl::DEPTH_STENCIL_ATTACHMENT,
+                    gl::RENDERBUFFER,
+                    target.depth_stencil,
+                );
+            }
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err(JellyError::IncompleteFramebuffer(status));
+            }
+        }
+
+        Ok(target)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.texture.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.texture.height()
+    }
+
+    /// Color attachment, only valid while the target is alive
+    pub fn texture(&self) -> Texture {
+        self.texture
+    }
+
+    /// Whole target as a viewport
+    pub fn viewport(&self) -> Viewport {
+        Viewport::new(0, 0, self.width() as i32, self.height() as i32)
+    }
+
+    /// Draw into the target from now on, the viewport covers the whole target
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+        }
+        self.viewport().apply();
+    }
+
+    /// Draw into the window again, the caller restores its viewport
+    pub fn unbind() {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    /// Copy the target into `viewport` of the window, stretched with nearest filtering
+    pub fn blit_to_screen(&self, viewport: &Viewport) {
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, 0);
+            gl::BlitFramebuffer(
+                0,
+                0,
+                self.width() as i32,
+                self.height() as i32,
+                viewport.x,
+                viewport.y,
+                viewport.x + viewport.width,
+                viewport.y + viewport.height,
+                gl::COLOR_BUFFER_BIT,
+                gl::NEAREST,
+            );
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+}
+
+impl Drop for RenderTarget {
+    fn drop(&mut self) {
+        unsafe {
+            if self.depth_stencil != 0 {
+                gl::DeleteRenderbuffers(1, &self.depth_stencil);
+            }
+            gl::DeleteFramebuffers(1, &self.fbo);
+        }
+        self.texture.delete();
+    }
+}
@@ -0,0 +1,341 @@
+use std::f32::consts::TAU;
+
+use super::prelude::{Color, ColoredVertex, SpriteBatch, Texture};
+use crate::math::prelude::{Matrix4x4, Vec2, Vec3};
+
+/// Pixels covered by each segment of a circle when `segments` is `None`
+const SEGMENT_LENGTH: f32 = 4.0;
+const MIN_SEGMENTS: u32 = 8;
+const MAX_SEGMENTS: u32 = 128;
+/// Joins longer than this many times the half thickness are beveled
+const MITER_LIMIT: f32 = 4.0;
+
+/// How the segments of a polyline meet
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    /// Edges extended until they meet, beveled past a sharp angle
+    #[default]
+    Miter,
+    Bevel,
+    Round,
+}
+
+/// Draws colored shapes through the `SpriteBatch`, with a white texture.
+///
+/// Shapes are queued with the quads around them, so they keep the draw order
+/// and share their draw calls when nothing else is textured in between.
+/// Outlines are centered on the edges of the shape.
+#[derive(Clone)]
+pub struct ShapeRenderer {
+    white: Texture,
+    /// Applied to every point, e.g. to draw in a rotated frame
+    pub transform: Matrix4x4,
+    /// Only cameras seeing this layer draw the shapes, see `LayerMask`
+    pub layer: u8,
+    /// Segments of circles and ellipses, `None` picks them from the radius
+    pub segments: Option<u32>,
+    pub join: LineJoin,
+}
+
+impl ShapeRenderer {
+    pub fn new() -> ShapeRenderer {
+        let white = Texture::from_pixels(1, 1, &[255, 255, 255, 255]).expect("one RGBA8 pixel");
+
+        ShapeRenderer::with_texture(white)
+    }
+
+    /// Renderer for a headless batch, which never draws
+    pub(crate) fn headless() -> ShapeRenderer {
+        ShapeRenderer::with_texture(Texture::headless(1, 1))
+    }
+
+    fn with_texture(white: Texture) -> ShapeRenderer {
+        ShapeRenderer {
+            white,
+            transform: Matrix4x4::identity(),
+            layer: 0,
+            segments: None,
+            join: LineJoin::default(),
+        }
+    }
+
+    /// The 1x1 white texture shapes are drawn with
+    pub fn texture(&self) -> Texture {
+        self.white
+    }
+
+    /// `position` is the top left corner
+    pub fn fill_rect(&self, batch: &mut SpriteBatch, position: Vec2, size: Vec2, color: Color) {
+        let corners = rect_corners(position, size);
+        self.quad(batch, corners, color);
+    }
+
+    pub fn stroke_rect(
+        &self,
+        batch: &mut SpriteBatch,
+        position: Vec2,
+        size: Vec2,
+        thickness: f32,
+        color: Color,
+    ) {
+        self.stroke_polygon(batch, &rect_corners(position, size), thickness, color);
+    }
+
+    pub fn fill_circle(&self, batch: &mut SpriteBatch, center: Vec2, radius: f32, color: Color) {
+        self.fill_ellipse(batch, center, Vec2::new(radius, radius), color);
+    }
+
+    pub fn stroke_circle(
+        &self,
+        batch: &mut SpriteBatch,
+        center: Vec2,
+        radius: f32,
+        thickness: f32,
+        color: Color,
+    ) {
+        self.stroke_ellipse(batch, center, Vec2::new(radius, radius), thickness, color);
+    }
+
+    pub fn fill_ellipse(&self, batch: &mut SpriteBatch, center: Vec2, radii: Vec2, color: Color) {
+        self.fill_polygon(batch, &self.ellipse_points(center, radii), color);
+    }
+
+    pub fn stroke_ellipse(
+        &self,
+        batch: &mut SpriteBatch,
+        center: Vec2,
+        radii: Vec2,
+        thickness: f32,
+        color: Color,
+    ) {
+        let points = self.ellipse_points(center, radii);
+        self.stroke_polygon(batch, &points, thickness, color);
+    }
+
+    /// Segment with square ends, flush with `from` and `to`
+    pub fn line(
+        &self,
+        batch: &mut SpriteBatch,
+        from: Vec2,
+        to: Vec2,
+        thickness: f32,
+        color: Color,
+    ) {
+        self.polyline(batch, &[from, to], thickness, color);
+    }
+
+    /// Connected segments, joined with `join`
+    pub fn polyline(&self, batch: &mut SpriteBatch, points: &[Vec2], thickness: f32, color: Color) {
+        self.stroke(batch, points, false, thickness, color);
+    }
+
+    pub fn fill_triangle(&self, batch: &mut SpriteBatch, a: Vec2, b: Vec2, c: Vec2, color: Color) {
+        self.quad(batch, [a, b, c, c], color);
+    }
+
+    pub fn stroke_triangle(
+        &self,
+        batch: &mut SpriteBatch,
+        points: [Vec2; 3],
+        thickness: f32,
+        color: Color,
+    ) {
+        self.stroke_polygon(batch, &points, thickness, color);
+    }
+
+    /// `points` must outline a convex polygon, in either winding
+    pub fn fill_polygon(&self, batch: &mut SpriteBatch, points: &[Vec2], color: Color) {
+        if points.len() < 3 {
+            return;
+        }
+
+        // Fan around the first point, two triangles per quad
+        let first = points[0];
+        let mut index = 1;
+        while index + 1 < points.len() {
+            let b = points[index];
+            let c = points[index + 1];
+            let d = points.get(index + 2).copied().unwrap_or(c);
+            self.quad(batch, [b, c, d, first], color);
+            index += 2;
+        }
+    }
+
+    /// Closed outline through `points`
+    pub fn stroke_polygon(
+        &self,
+        batch: &mut SpriteBatch,
+        points: &[Vec2],
+        thickness: f32,
+        color: Color,
+    ) {
+        self.stroke(batch, points, true, thickness, color);
+    }
+
+    fn stroke(
+        &self,
+        batch: &mut SpriteBatch,
+        points: &[Vec2],
+        closed: bool,
+        thickness: f32,
+        color: Color,
+    ) {
+        let mut points: Vec<Vec2> = points.to_vec();
+        points.dedup();
+        if closed && points.len() > 2 && points.first() == points.last() {
+            points.pop();
+        }
+
+        let count = points.len();
+        if count < 2 || thickness <= 0.0 {
+            return;
+        }
+
+        let half = thickness / 2.0;
+        let closed = closed && count > 2;
+        let normal = |segment: usize| {
+            (points[(segment + 1) % count] - points[segment])
+                .normalized()
+                .perpendicular()
+        };
+
+        // Edges of the stroke at each point, on both sides of the path, where
+        // the incoming segment ends and where the outgoing one starts
+        let mut sections = Vec::with_capacity(count);
+        for (index, &point) in points.iter().enumerate() {
+            let incoming = (closed || index > 0).then(|| normal((index + count - 1) % count));
+            let outgoing = (closed || index + 1 < count).then(|| normal(index));
+
+            sections.push(match (incoming, outgoing) {
+                (Some(incoming), Some(outgoing)) => {
+                    self.join(batch, point, incoming, outgoing, half, color)
+                }
+                (Some(normal), None) | (None, Some(normal)) => {
+                    let section = (point + normal * half, point - normal * half);
+                    (section, section)
+                }
+                (None, None) => unreachable!(),
+            });
+        }
+
+        let segments = if closed { count } else { count - 1 };
+        for segment in 0..segments {
+            let (_, start) = sections[segment];
+            let (end, _) = sections[(segment + 1) % count];
+            self.quad(batch, [start.0, start.1, end.1, end.0], color);
+        }
+    }
+
+    /// Sections where two segments meet at `point`, filling the gap on the
+    /// outer side of the turn when they're not mitered
+    fn join(
+        &self,
+        batch: &mut SpriteBatch,
+        point: Vec2,
+        incoming: Vec2,
+        outgoing: Vec2,
+        half: f32,
+        color: Color,
+    ) -> ((Vec2, Vec2), (Vec2, Vec2)) {
+        let miter = (incoming + outgoing).normalized();
+        let cosine = miter.dot(incoming);
+        if self.join == LineJoin::Miter && cosine > 1.0 / MITER_LIMIT {
+            let offset = miter * (half / cosine);
+            let section = (point + offset, point - offset);
+            return (section, section);
+        }
+
+        let end = (point + incoming * half, point - incoming * half);
+        let start = (point + outgoing * half, point - outgoing * half);
+
+        // Normals point to the right of the direction on screen, the gap is
+        // on the side the path turns away from
+        let turns_left = incoming.perpendicular().dot(outgoing) < 0.0;
+        let (from, to) = if turns_left {
+            (end.0, start.0)
+        } else {
+            (end.1, start.1)
+        };
+
+        match self.join {
+            LineJoin::Round => {
+                let from_angle = (from.y - point.y).atan2(from.x - point.x);
+                let mut sweep = (to.y - point.y).atan2(to.x - point.x) - from_angle;
+                if sweep > TAU / 2.0 {
+                    sweep -= TAU;
+                } else if sweep < -TAU / 2.0 {
+                    sweep += TAU;
+                }
+
+                let steps = (self.segment_count(half) as f32 * sweep.abs() / TAU).ceil() as u32;
+                let mut previous = from;
+                for step in 1..=steps.max(1) {
+                    let angle = from_angle + sweep * step as f32 / steps.max(1) as f32;
+                    let next = point + Vec2::new(angle.cos(), angle.sin()) * half;
+                    self.quad(batch, [point, previous, next, next], color);
+                    previous = next;
+                }
+            }
+            _ => self.quad(batch, [point, from, to, to], color),
+        }
+
+        (end, start)
+    }
+
+    fn ellipse_points(&self, center: Vec2, radii: Vec2) -> Vec<Vec2> {
+        let segments = self.segment_count(radii.x.abs().max(radii.y.abs()));
+
+        (0..segments)
+            .map(|index| {
+                let angle = TAU * index as f32 / segments as f32;
+                center + Vec2::new(angle.cos() * radii.x, angle.sin() * radii.y)
+            })
+            .collect()
+    }
+
+    fn segment_count(&self, radius: f32) -> u32 {
+        match self.segments {
+            Some(segments) => segments.max(3),
+            None => {
+                ((radius * TAU / SEGMENT_LENGTH).ceil() as u32).clamp(MIN_SEGMENTS, MAX_SEGMENTS)
+            }
+        }
+    }
+
+    fn quad(&self, batch: &mut SpriteBatch, points: [Vec2; 4], color: Color) {
+        let corner = |point: Vec2| {
+            ColoredVertex::new(
+                self.transform
+                    .transform_point(Vec3::new(point.x, point.y, 0.0)),
+                Vec2::new(0.5, 0.5),
+                color,
+            )
+        };
+
+        batch.draw_corners(
+            &self.white,
+            [
+                corner(points[0]),
+                corner(points[1]),
+                corner(points[2]),
+                corner(points[3]),
+            ],
+            self.layer,
+        );
+    }
+}
+
+fn rect_corners(position: Vec2, size: Vec2) -> [Vec2; 4] {
+    [
+        position,
+        position + Vec2::new(0.0, size.y),
+        position + size,
+        position + Vec2::new(size.x, 0.0),
+    ]
+}
+
+impl Default for ShapeRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
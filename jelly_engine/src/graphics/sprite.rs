@@ -1,135 +1,143 @@
-use super::prelude::{Color, Material, Vertex};
-use crate::gl_utilities::prelude::{AttributeInfo, GLbuffer, Shader};
+use super::prelude::{Material, TextureRegion, UvRect, Vertex};
+use crate::error::Result;
+use crate::gl_utilities::prelude::{GLbuffer, ShaderManager, VertexFormat};
 use crate::math::prelude::{Matrix4x4, Vec3};
 
-pub struct Sprite<'a> {
+pub struct Sprite {
     pub name: String,
 
     pub width: f32,
     pub height: f32,
 
     pub origin: Vec3,
+    /// Only cameras seeing this layer draw the sprite through the
+    /// `SpriteBatch`, see `LayerMask`
+    pub layer: u8,
 
-    color: Color,
-
-    a_position_location: gl::types::GLuint,
-    a_tex_coord_location: gl::types::GLuint,
-    u_tint_location: gl::types::GLint,
-    u_model_location: gl::types::GLint,
-    u_diffuse_location: gl::types::GLint,
+    attribute_locations: Vec<gl::types::GLuint>,
 
     buffer: GLbuffer,
-    vertices: [Vertex; 6],
+    vertices: [Vertex; 4],
+
+    uv: UvRect,
+    flip_x: bool,
+    flip_y: bool,
 
-    shader: &'a Shader,
-    material: Material<'a>,
+    /// Swap it with `set_material` to change the shader
+    pub material: Material,
 }
 
-impl<'a> Sprite<'a> {
+impl Sprite {
+    /// Sprite drawn with the shader of `material`, which must be registered
+    /// and have a uniform for each param of the material
     pub fn new(
         name: &str,
-        shader: &'a Shader,
-        material: Material<'a>,
+        shaders: &ShaderManager,
+        material: Material,
         width: Option<f32>,
         height: Option<f32>,
-    ) -> Sprite<'a> {
-        Sprite {
+    ) -> Result<Sprite> {
+        let shader = shaders.get(material.shader())?;
+        material.validate(shader)?;
+
+        Ok(Sprite {
             name: String::from(name),
-            width: match width {
-                Some(w) => w,
-                _ => 10.0,
-            },
-            height: match height {
-                Some(h) => h,
-                _ => 10.0,
-            },
+            width: width.unwrap_or(10.0),
+            height: height.unwrap_or(10.0),
             origin: Vec3::zero(),
+            layer: 0,
 
-            color: Color::from_palette("red").unwrap(),
-
-            a_position_location: shader.get_attribute_location("a_position"),
-            a_tex_coord_location: shader.get_attribute_location("a_tex_coord"),
-            u_tint_location: shader.get_uniform_location("u_tint"),
-            u_model_location: shader.get_uniform_location("u_model"),
-            u_diffuse_location: shader.get_uniform_location("u_diffuse"),
+            attribute_locations: Vertex::layout().locations(shader)?,
 
             buffer: GLbuffer::new(),
-            vertices: [Vertex::new(0.0, 0.0, 0.0, 0.0, 0.0); 6],
+            vertices: [Vertex::new(0.0, 0.0, 0.0, 0.0, 0.0); 4],
+
+            uv: UvRect::full(),
+            flip_x: false,
+            flip_y: false,
 
-            shader,
             material,
-        }
+        })
     }
 
     pub fn load(&mut self) {
-        self.buffer.configure(
-            vec![
-                AttributeInfo {
-                    location: self.a_position_location,
-                    component_size: 3,
-                },
-                AttributeInfo {
-                    location: self.a_tex_coord_location,
-                    component_size: 2,
-                },
-            ],
-            false,
-        );
+        self.buffer
+            .configure_for::<Vertex>(&self.attribute_locations);
+
+        self.buffer.upload_indices(&[0, 1, 2, 2, 3, 0]);
+        self.calculate_vertices();
+    }
+
+    /// Draw with `material` from now on, its shader may differ from the current one
+    pub fn set_material(&mut self, shaders: &ShaderManager, material: Material) -> Result<()> {
+        let shader = shaders.get(material.shader())?;
+        material.validate(shader)?;
+
+        if material.shader() != self.material.shader() {
+            self.attribute_locations = Vertex::layout().locations(shader)?;
+            self.buffer
+                .configure_for::<Vertex>(&self.attribute_locations);
+        }
+        self.material = material;
+
+        Ok(())
+    }
+
+    /// Show `region` instead of the whole texture
+    pub fn set_region(&mut self, region: &TextureRegion) {
+        self.material.texture = region.texture;
+        self.uv = region.uv();
 
         self.calculate_vertices();
     }
 
+    /// Mirror the sprite, on top of any flip of its region
+    pub fn set_flip(&mut self, flip_x: bool, flip_y: bool) {
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+
+        self.calculate_vertices();
+    }
+
+    /// Texture coordinates drawn, flips applied
+    pub fn uv(&self) -> UvRect {
+        self.uv.flipped(self.flip_x, self.flip_y)
+    }
+
     fn calculate_vertices(&mut self) {
         let min_x = -(self.width * self.origin.x);
         let max_x = self.width * (1.0 - self.origin.x);
         let min_y = -(self.height * self.origin.y);
         let max_y = self.height * (1.0 - self.origin.y);
 
-        self.vertices[0] = Vertex::new(min_x, min_y, 0.0, 0.0, 0.0);
-        self.vertices[1] = Vertex::new(min_x, max_y, 0.0, 0.0, 1.0);
-        self.vertices[2] = Vertex::new(max_x, max_y, 0.0, 1.0, 1.0);
-        self.vertices[3] = Vertex::new(max_x, max_y, 0.0, 1.0, 1.0);
-        self.vertices[4] = Vertex::new(max_x, min_y, 0.0, 1.0, 0.0);
-        self.vertices[5] = Vertex::new(min_x, min_y, 0.0, 0.0, 0.0);
-
-        let vertices_map = self
-            .vertices
-            .iter()
-            .flat_map(|v| {
-                vec![
-                    v.position.x,
-                    v.position.y,
-                    v.position.z,
-                    v.tex_coord.x,
-                    v.tex_coord.y,
-                ]
-            })
-            .collect::<Vec<f32>>();
-
-        self.buffer.upload(&vertices_map);
+        let uv = self.uv();
+
+        self.vertices[0] = Vertex::new(min_x, min_y, 0.0, uv.u0, uv.v0);
+        self.vertices[1] = Vertex::new(min_x, max_y, 0.0, uv.u0, uv.v1);
+        self.vertices[2] = Vertex::new(max_x, max_y, 0.0, uv.u1, uv.v1);
+        self.vertices[3] = Vertex::new(max_x, min_y, 0.0, uv.u1, uv.v0);
+
+        self.buffer.upload(&self.vertices);
     }
 
-    pub fn draw(&self, model: &Matrix4x4) {
+    /// Draw now with the shader of the material, which also needs the
+    /// `u_model` uniform. Fails when a param has no uniform of its type
+    pub fn draw(&self, shaders: &ShaderManager, model: &Matrix4x4) -> Result<()> {
+        let shader = shaders.get(self.material.shader())?;
+        shader.use_shader();
+
+        self.material.apply(shader)?;
         unsafe {
             gl::UniformMatrix4fv(
-                self.u_model_location, // uniform position (u_projection)
+                shader.get_uniform_location("u_model")?,
                 1,
                 gl::FALSE,
                 model.data.as_ptr(),
             );
-
-            gl::Uniform4f(
-                self.u_tint_location, // uniform position (u_color)
-                self.material.tint.r,
-                self.material.tint.g,
-                self.material.tint.b,
-                self.material.tint.a,
-            );
-
-            self.material.texture.activate();
-            gl::Uniform1i(self.u_diffuse_location, 0);
         }
 
         self.buffer.draw();
+
+        Ok(())
     }
 }
@@ -0,0 +1,377 @@
+use std::collections::{HashMap, HashSet};
+
+use super::prelude::{Color, ColoredVertex, LayerMask, Sprite, Texture, TextureRegion};
+use crate::error::Result;
+use crate::gl_utilities::prelude::{BufferUsage, GLbuffer, Shader, VertexFormat};
+use crate::math::prelude::{Matrix4x4, Vec2, Vec3};
+
+/// Quads above this count are split over several draw calls
+const MAX_QUADS_PER_BATCH: usize = 2048;
+const VERTICES_PER_QUAD: usize = 4;
+const INDICES_PER_QUAD: usize = 6;
+
+/// Part of a texture to sample, in 0..1 texture coordinates
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct UvRect {
+    pub u0: f32,
+    pub v0: f32,
+    pub u1: f32,
+    pub v1: f32,
+}
+
+impl UvRect {
+    pub fn new(u0: f32, v0: f32, u1: f32, v1: f32) -> UvRect {
+        UvRect { u0, v0, u1, v1 }
+    }
+
+    /// The whole texture
+    pub fn full() -> UvRect {
+        UvRect::new(0.0, 0.0, 1.0, 1.0)
+    }
+
+    /// Swap the horizontal and/or vertical texture coordinates
+    pub fn flipped(self, flip_x: bool, flip_y: bool) -> UvRect {
+        let (u0, u1) = if flip_x {
+            (self.u1, self.u0)
+        } else {
+            (self.u0, self.u1)
+        };
+        let (v0, v1) = if flip_y {
+            (self.v1, self.v0)
+        } else {
+            (self.v0, self.v1)
+        };
+
+        UvRect::new(u0, v0, u1, v1)
+    }
+}
+
+impl Default for UvRect {
+    fn default() -> Self {
+        UvRect::full()
+    }
+}
+
+/// A textured rectangle submitted to a `SpriteBatch`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Quad {
+    pub width: f32,
+    pub height: f32,
+    /// Pivot of the quad, (0, 0) is the top left corner and (1, 1) the bottom right one
+    pub origin: Vec2,
+    pub uv: UvRect,
+    pub tint: Color,
+    /// Only cameras seeing this layer draw the quad, see `LayerMask`
+    pub layer: u8,
+}
+
+impl Quad {
+    pub fn new(width: f32, height: f32) -> Quad {
+        Quad {
+            width,
+            height,
+            origin: Vec2::zero(),
+            uv: UvRect::full(),
+            tint: Color::white(),
+            layer: 0,
+        }
+    }
+
+    /// Quad the size of `region`, in pixels, showing it
+    pub fn from_region(region: &TextureRegion) -> Quad {
+        Quad {
+            uv: region.uv(),
+            ..Quad::new(region.width as f32, region.height as f32)
+        }
+    }
+}
+
+/// Draw calls and quads submitted during a frame
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct BatchStats {
+    pub draw_calls: u32,
+    pub quads: u32,
+}
+
+struct BatchShader {
+    program: gl::types::GLuint,
+    attribute_locations: Vec<gl::types::GLuint>,
+    u_projection_location: gl::types::GLint,
+    u_diffuse_location: gl::types::GLint,
+}
+
+/// Collects quads and draws every consecutive run sharing a texture and a
+/// shader with a single draw call.
+///
+/// Quads are transformed on the CPU, so a batch is flushed only when the
+/// texture or shader changes, when it is full, or at the end of the frame.
+/// The engine calls `begin` and `end` around `Game::render`, anything drawn
+/// directly (e.g. `Sprite::draw`) in between should `flush` first to keep the
+/// draw order.
+pub struct SpriteBatch {
+    shader: Option<BatchShader>,
+    // One VAO per shader since attribute locations differ between programs
+    buffers: HashMap<gl::types::GLuint, GLbuffer>,
+    texture: Option<Texture>,
+    projection: Matrix4x4,
+    layers: LayerMask,
+
+    vertices: Vec<ColoredVertex>,
+
+    frame_stats: BatchStats,
+    stats: BatchStats,
+
+    // Sprites already warned about for a material the batch can't draw
+    unbatchable_sprites: HashSet<String>,
+
+    headless: bool,
+}
+
+impl SpriteBatch {
+    pub fn new() -> SpriteBatch {
+        SpriteBatch {
+            shader: None,
+            buffers: HashMap::new(),
+            texture: None,
+            projection: Matrix4x4::identity(),
+            layers: LayerMask::ALL,
+
+            vertices: Vec::with_capacity(MAX_QUADS_PER_BATCH * VERTICES_PER_QUAD),
+
+            frame_stats: BatchStats::default(),
+            stats: BatchStats::default(),
+
+            unbatchable_sprites: HashSet::new(),
+
+            headless: false,
+        }
+    }
+
+    /// Batch that only counts quads and draw calls, without touching GL
+    pub(crate) fn headless() -> SpriteBatch {
+        SpriteBatch {
+            headless: true,
+            ..SpriteBatch::new()
+        }
+    }
+
+    /// Draw the next quads with `shader`, which needs the `a_position`,
+    /// `a_tex_coord`, `a_color`, `u_projection` and `u_diffuse` inputs of `batch.vert`
+    pub fn set_shader(&mut self, shader: &Shader) -> Result<()> {
+        if self
+            .shader
+            .as_ref()
+            .is_some_and(|current| current.program == shader.program)
+        {
+            return Ok(());
+        }
+
+        let batch_shader = BatchShader {
+            program: shader.program,
+            attribute_locations: ColoredVertex::layout().locations(shader)?,
+            u_projection_location: shader.get_uniform_location("u_projection")?,
+            u_diffuse_location: shader.get_uniform_location("u_diffuse")?,
+        };
+
+        self.flush();
+        self.shader = Some(batch_shader);
+
+        Ok(())
+    }
+
+    /// Start a new frame
+    pub fn begin(&mut self, projection: Matrix4x4) {
+        self.vertices.clear();
+        self.texture = None;
+        self.projection = projection;
+        self.layers = LayerMask::ALL;
+        self.frame_stats = BatchStats::default();
+    }
+
+    /// Draw the next quads with `projection`, the queued ones are flushed first
+    pub fn set_projection(&mut self, projection: Matrix4x4) {
+        self.flush();
+        self.projection = projection;
+    }
+
+    /// Skip the next quads whose layer isn't in `layers`
+    pub fn set_layers(&mut self, layers: LayerMask) {
+        self.layers = layers;
+    }
+
+    pub fn layers(&self) -> LayerMask {
+        self.layers
+    }
+
+    /// Queue a quad of `texture`, placed by `model`
+    pub fn draw(&mut self, texture: &Texture, quad: &Quad, model: &Matrix4x4) {
+        if !self.layers.contains(quad.layer) {
+            return;
+        }
+
+        let min_x = -(quad.width * quad.origin.x);
+        let max_x = quad.width * (1.0 - quad.origin.x);
+        let min_y = -(quad.height * quad.origin.y);
+        let max_y = quad.height * (1.0 - quad.origin.y);
+
+        let corner = |x: f32, y: f32, u: f32, v: f32| {
+            ColoredVertex::new(
+                model.transform_point(Vec3::new(x, y, 0.0)),
+                Vec2::new(u, v),
+                quad.tint,
+            )
+        };
+
+        let uv = &quad.uv;
+        let top_left = corner(min_x, min_y, uv.u0, uv.v0);
+        let bottom_left = corner(min_x, max_y, uv.u0, uv.v1);
+        let bottom_right = corner(max_x, max_y, uv.u1, uv.v1);
+        let top_right = corner(max_x, min_y, uv.u1, uv.v0);
+
+        self.queue(texture, [top_left, bottom_left, bottom_right, top_right]);
+    }
+
+    /// Queue four corners already placed, in the top left, bottom left, bottom
+    /// right, top right order. They're drawn as the triangles (0, 1, 2) and
+    /// (2, 3, 0), so a triangle can repeat its last corner
+    pub fn draw_corners(&mut self, texture: &Texture, corners: [ColoredVertex; 4], layer: u8) {
+        if self.layers.contains(layer) {
+            self.queue(texture, corners);
+        }
+    }
+
+    fn queue(&mut self, texture: &Texture, corners: [ColoredVertex; 4]) {
+        let texture_changed = self
+            .texture
+            .as_ref()
+            .is_none_or(|current| current.id() != texture.id());
+
+        if texture_changed || self.vertices.len() >= MAX_QUADS_PER_BATCH * VERTICES_PER_QUAD {
+            self.flush();
+            self.texture = Some(*texture);
+        }
+
+        self.vertices.extend_from_slice(&corners);
+
+        self.frame_stats.quads += 1;
+    }
+
+    /// Queue a sprite with its size, origin, layer, tint and texture. The batch
+    /// draws with its own shader, a sprite whose material has another shader
+    /// or params is drawn without them, with a warning: use `Sprite::draw`
+    pub fn draw_sprite(&mut self, sprite: &Sprite, model: &Matrix4x4) {
+        if !sprite.material.is_plain() && self.unbatchable_sprites.insert(sprite.name.clone()) {
+            log::warn!(
+                "Sprite {} is batched without the {} shader and params of its material, draw it with Sprite::draw",
+                sprite.name,
+                sprite.material.shader()
+            );
+        }
+
+        let quad = Quad {
+            width: sprite.width,
+            height: sprite.height,
+            origin: Vec2::new(sprite.origin.x, sprite.origin.y),
+            uv: sprite.uv(),
+            tint: sprite.material.tint,
+            layer: sprite.layer,
+        };
+
+        self.draw(&sprite.material.texture, &quad, model);
+    }
+
+    /// Queue a region at its size in pixels
+    pub fn draw_region(&mut self, region: &TextureRegion, model: &Matrix4x4) {
+        self.draw(&region.texture, &Quad::from_region(region), model);
+    }
+
+    /// Draw the queued quads now
+    pub fn flush(&mut self) {
+        if self.vertices.is_empty() {
+            return;
+        }
+
+        self.frame_stats.draw_calls += 1;
+
+        if !self.headless {
+            self.submit();
+        }
+
+        self.vertices.clear();
+    }
+
+    /// Flush the remaining quads and publish the frame statistics
+    pub fn end(&mut self) {
+        self.flush();
+        self.stats = self.frame_stats;
+    }
+
+    /// Statistics of the last finished frame
+    pub fn stats(&self) -> BatchStats {
+        self.stats
+    }
+
+    fn submit(&mut self) {
+        let (shader, texture) = match (&self.shader, &self.texture) {
+            (Some(shader), Some(texture)) => (shader, texture),
+            (None, _) => {
+                log::error!(
+                    "SpriteBatch flushed without a shader, dropping {} quads",
+                    self.vertices.len() / VERTICES_PER_QUAD
+                );
+                return;
+            }
+            (_, None) => return,
+        };
+
+        let buffer = self.buffers.entry(shader.program).or_insert_with(|| {
+            let mut buffer = GLbuffer::with_usage(BufferUsage::Dynamic);
+            buffer.configure_for::<ColoredVertex>(&shader.attribute_locations);
+            buffer.reserve::<ColoredVertex>(MAX_QUADS_PER_BATCH * VERTICES_PER_QUAD);
+            buffer.upload_indices(&quad_indices(MAX_QUADS_PER_BATCH));
+            buffer
+        });
+        buffer.upload(&self.vertices);
+
+        unsafe {
+            // Leave the program in use for whoever draws after the batch
+            let mut previous_program = 0;
+            gl::GetIntegerv(gl::CURRENT_PROGRAM, &mut previous_program);
+
+            gl::UseProgram(shader.program);
+            gl::UniformMatrix4fv(
+                shader.u_projection_location,
+                1,
+                gl::FALSE,
+                self.projection.data.as_ptr(),
+            );
+
+            texture.activate();
+            gl::Uniform1i(shader.u_diffuse_location, 0);
+
+            buffer.draw_range(
+                0,
+                self.vertices.len() / VERTICES_PER_QUAD * INDICES_PER_QUAD,
+            );
+
+            gl::BindVertexArray(0);
+            gl::UseProgram(previous_program as gl::types::GLuint);
+        }
+    }
+}
+
+/// Two triangles per quad, over vertices laid out top left, bottom left, bottom right, top right
+pub(crate) fn quad_indices(quads: usize) -> Vec<u32> {
+    (0..quads as u32)
+        .flat_map(|quad| {
+            let first = quad * VERTICES_PER_QUAD as u32;
+            [first, first + 1, first + 2, first + 2, first + 3, first]
+        })
+        .collect()
+}
+
+impl Default for SpriteBatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,4 +1,8 @@
+use std::collections::HashMap;
+use std::path::Path;
+
 use crate::assets::prelude::image_loader;
+use crate::error::{JellyError, Result};
 
 const LEVEL: i32 = 0;
 const BORDER: i32 = 0;
@@ -11,11 +15,49 @@ pub struct Texture {
 }
 
 impl Texture {
-    pub fn new(image_name: &str) -> Texture {
-        let img = image_loader::load(image_name);
+    pub fn new(image_name: &str) -> Result<Texture> {
+        let img = image_loader::load(image_name)?;
+
+        Texture::from_pixels(img.width, img.height, &img.data)
+    }
+
+    /// Load an image from a path instead of the images assets folder
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Texture> {
+        let img = image_loader::load_path(path.as_ref())?;
+
+        Texture::from_pixels(img.width, img.height, &img.data)
+    }
+
+    /// Create a texture from raw RGBA8 pixels, `data` must hold at least
+    /// `width * height * 4` bytes
+    pub fn from_pixels(width: u32, height: u32, data: &[u8]) -> Result<Texture> {
+        check_pixels(width, height, data)?;
+
+        Ok(Texture::create(
+            width,
+            height,
+            data.as_ptr() as *const gl::types::GLvoid,
+        ))
+    }
+
+    /// Create a texture with undefined content, e.g. to render into
+    pub fn empty(width: u32, height: u32) -> Texture {
+        Texture::create(width, height, std::ptr::null())
+    }
+
+    /// Texture without GL object, for headless batches which never draw
+    pub(crate) fn headless(width: u32, height: u32) -> Texture {
+        Texture {
+            texture_id: 0,
+            width,
+            height,
+        }
+    }
+
+    fn create(width: u32, height: u32, data: *const gl::types::GLvoid) -> Texture {
         let mut t = Texture {
-            width: img.width,
-            height: img.height,
+            width,
+            height,
             texture_id: 0,
         };
 
@@ -32,7 +74,7 @@ impl Texture {
                 BORDER,
                 gl::RGBA,
                 gl::UNSIGNED_BYTE,
-                img.data.as_ptr() as *const gl::types::GLvoid,
+                data,
             );
 
             gl::GenerateMipmap(gl::TEXTURE_2D);
@@ -45,10 +87,121 @@ impl Texture {
         t
     }
 
+    /// Replace the pixels with raw RGBA8 `data`, of the same size as the texture
+    pub fn update(&self, data: &[u8]) -> Result<()> {
+        check_pixels(self.width, self.height, data)?;
+
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.texture_id);
+
+            gl::TexSubImage2D(
+                gl::TEXTURE_2D,
+                LEVEL,
+                0,
+                0,
+                self.width as i32,
+                self.height as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                data.as_ptr() as *const gl::types::GLvoid,
+            );
+
+            gl::GenerateMipmap(gl::TEXTURE_2D);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+
+        Ok(())
+    }
+
+    /// GL texture name
+    pub fn id(&self) -> u32 {
+        self.texture_id
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Free the GL texture, every copy of it becomes invalid
+    pub fn delete(self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.texture_id);
+        }
+    }
+
     pub fn activate(&self) {
+        self.activate_unit(0);
+    }
+
+    /// Bind to texture unit `unit`, for shaders sampling several textures
+    pub fn activate_unit(&self, unit: u32) {
         unsafe {
-            gl::ActiveTexture(gl::TEXTURE0);
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
             gl::BindTexture(gl::TEXTURE_2D, self.texture_id);
         }
     }
 }
+
+// GL reads `width * height * 4` bytes, whatever the length of the slice
+fn check_pixels(width: u32, height: u32, data: &[u8]) -> Result<()> {
+    if data.len() < width as usize * height as usize * 4 {
+        return Err(JellyError::PixelDataSize {
+            width,
+            height,
+            len: data.len(),
+        });
+    }
+
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct TextureManager {
+    textures: HashMap<String, Texture>,
+}
+
+impl TextureManager {
+    pub fn new() -> TextureManager {
+        TextureManager {
+            textures: HashMap::new(),
+        }
+    }
+
+    /// Load `image_name` from the assets folder and store it under `name`
+    pub fn load(&mut self, name: &str, image_name: &str) -> Result<Texture> {
+        Ok(self.insert(name, Texture::new(image_name)?))
+    }
+
+    pub fn insert(&mut self, name: &str, texture: Texture) -> Texture {
+        self.textures.insert(String::from(name), texture);
+
+        texture
+    }
+
+    pub fn get(&self, name: &str) -> Result<Texture> {
+        match self.textures.get(name) {
+            Some(&texture) => Ok(texture),
+            _ => Err(JellyError::TextureNotFound(String::from(name))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixels_must_cover_the_texture() {
+        assert!(check_pixels(2, 2, &[0; 16]).is_ok());
+        assert!(check_pixels(2, 2, &[0; 20]).is_ok());
+        assert!(matches!(
+            check_pixels(2, 2, &[0; 15]),
+            Err(JellyError::PixelDataSize { len: 15, .. })
+        ));
+        assert!(Texture::from_pixels(4, 4, &[255; 4]).is_err());
+    }
+}
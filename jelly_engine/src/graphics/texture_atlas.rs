@@ -0,0 +1,242 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use serde::Deserialize;
+
+use super::prelude::{Texture, UvRect};
+use crate::error::{JellyError, Result};
+
+/// Sub-rectangle of a texture, in pixels
+#[derive(Debug, Copy, Clone)]
+pub struct TextureRegion {
+    pub texture: Texture,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl TextureRegion {
+    pub fn new(texture: Texture, x: u32, y: u32, width: u32, height: u32) -> TextureRegion {
+        TextureRegion {
+            texture,
+            x,
+            y,
+            width,
+            height,
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+
+    /// The whole texture
+    pub fn full(texture: Texture) -> TextureRegion {
+        TextureRegion::new(texture, 0, 0, texture.width(), texture.height())
+    }
+
+    /// Same region mirrored horizontally and/or vertically
+    pub fn flipped(mut self, flip_x: bool, flip_y: bool) -> TextureRegion {
+        self.flip_x ^= flip_x;
+        self.flip_y ^= flip_y;
+        self
+    }
+
+    /// Texture coordinates of the region, flips applied
+    pub fn uv(&self) -> UvRect {
+        let texture_width = self.texture.width().max(1) as f32;
+        let texture_height = self.texture.height().max(1) as f32;
+
+        UvRect::new(
+            self.x as f32 / texture_width,
+            self.y as f32 / texture_height,
+            (self.x + self.width) as f32 / texture_width,
+            (self.y + self.height) as f32 / texture_height,
+        )
+        .flipped(self.flip_x, self.flip_y)
+    }
+}
+
+/// Cells of a sprite sheet laid out on a regular grid
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Grid {
+    pub cell_width: u32,
+    pub cell_height: u32,
+    /// Pixels between the texture border and the first cells
+    pub margin: u32,
+    /// Pixels between two cells
+    pub spacing: u32,
+}
+
+impl Grid {
+    pub fn new(cell_width: u32, cell_height: u32) -> Grid {
+        Grid {
+            cell_width,
+            cell_height,
+            margin: 0,
+            spacing: 0,
+        }
+    }
+}
+
+/// Named regions of a single texture
+#[derive(Debug, Clone)]
+pub struct TextureAtlas {
+    texture: Texture,
+    regions: HashMap<String, TextureRegion>,
+}
+
+impl TextureAtlas {
+    pub fn new(texture: Texture) -> TextureAtlas {
+        TextureAtlas {
+            texture,
+            regions: HashMap::new(),
+        }
+    }
+
+    pub fn texture(&self) -> Texture {
+        self.texture
+    }
+
+    /// Add a region from its rectangle in pixels
+    pub fn add(&mut self, name: &str, x: u32, y: u32, width: u32, height: u32) -> TextureRegion {
+        let region = TextureRegion::new(self.texture, x, y, width, height);
+        self.regions.insert(String::from(name), region);
+
+        region
+    }
+
+    /// Cut the texture in cells, row by row, named `{prefix}{index}` starting at 0.
+    /// Cells not fully inside the texture are skipped.
+    pub fn slice_grid(&mut self, prefix: &str, grid: Grid) -> Vec<TextureRegion> {
+        let mut cells = Vec::new();
+
+        if grid.cell_width == 0 || grid.cell_height == 0 {
+            return cells;
+        }
+
+        let mut y = grid.margin;
+        while y + grid.cell_height + grid.margin <= self.texture.height() {
+            let mut x = grid.margin;
+            while x + grid.cell_width + grid.margin <= self.texture.width() {
+                let name = format!("{}{}", prefix, cells.len());
+                cells.push(self.add(&name, x, y, grid.cell_width, grid.cell_height));
+
+                x += grid.cell_width + grid.spacing;
+            }
+
+            y += grid.cell_height + grid.spacing;
+        }
+
+        cells
+    }
+
+    pub fn region(&self, name: &str) -> Result<TextureRegion> {
+        self.regions
+            .get(name)
+            .copied()
+            .ok_or_else(|| JellyError::RegionNotFound(String::from(name)))
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.regions.keys().map(String::as_str)
+    }
+
+    /// Read the frames of a TexturePacker JSON export (hash or array flavor).
+    ///
+    /// Frames are used as packed: trimmed frames lose their transparent border
+    /// and rotated frames are refused.
+    pub fn from_json(texture: Texture, source: &str) -> Result<TextureAtlas> {
+        let sheet = parse_sheet(source)?;
+
+        let mut atlas = TextureAtlas::new(texture);
+        for (name, frame) in sheet.frames.into_named() {
+            if frame.rotated {
+                return Err(JellyError::InvalidAtlas(format!(
+                    "frame {} is rotated, disable rotation when packing",
+                    name
+                )));
+            }
+
+            let rect = frame.frame;
+            atlas.add(&name, rect.x, rect.y, rect.w, rect.h);
+        }
+
+        Ok(atlas)
+    }
+
+    /// Load a TexturePacker JSON export along with the image it names,
+    /// which is looked up in the images assets folder
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<TextureAtlas> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(JellyError::AssetNotFound(path.to_path_buf()));
+        }
+
+        let source = fs::read_to_string(path)?;
+        let image = parse_sheet(&source)?
+            .meta
+            .and_then(|meta| meta.image)
+            .ok_or_else(|| JellyError::InvalidAtlas(String::from("missing meta.image")))?;
+
+        TextureAtlas::from_json(Texture::new(&image)?, &source)
+    }
+}
+
+// TexturePacker JSON layout, only the fields we use
+
+#[derive(Deserialize)]
+struct Sheet {
+    frames: Frames,
+    meta: Option<Meta>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Frames {
+    Hash(HashMap<String, Frame>),
+    Array(Vec<NamedFrame>),
+}
+
+impl Frames {
+    fn into_named(self) -> Vec<(String, Frame)> {
+        match self {
+            Frames::Hash(frames) => frames.into_iter().collect(),
+            Frames::Array(frames) => frames
+                .into_iter()
+                .map(|named| (named.filename, named.frame))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct NamedFrame {
+    filename: String,
+    #[serde(flatten)]
+    frame: Frame,
+}
+
+#[derive(Deserialize)]
+struct Frame {
+    frame: FrameRect,
+    #[serde(default)]
+    rotated: bool,
+}
+
+#[derive(Deserialize)]
+struct FrameRect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+struct Meta {
+    image: Option<String>,
+}
+
+fn parse_sheet(source: &str) -> Result<Sheet> {
+    serde_json::from_str(source).map_err(|e| JellyError::InvalidAtlas(e.to_string()))
+}
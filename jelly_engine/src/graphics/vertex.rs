@@ -1,7 +1,11 @@
 #![allow(clippy::many_single_char_names)]
+use super::prelude::Color;
+use crate::gl_utilities::prelude::{VertexFormat, VertexLayout};
 use crate::math::prelude::{Vec2, Vec3};
+use crate::vertex_layout;
 
 #[derive(Debug, Copy, Clone)]
+#[repr(C)]
 pub struct Vertex {
     pub position: Vec3,
     pub tex_coord: Vec2,
@@ -15,3 +19,42 @@ impl Vertex {
         }
     }
 }
+
+impl VertexFormat for Vertex {
+    fn layout() -> VertexLayout {
+        vertex_layout!(Vertex {
+            position => "a_position",
+            tex_coord => "a_tex_coord",
+        })
+    }
+}
+
+/// Vertex carrying its own tint, used by batched geometry
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+pub struct ColoredVertex {
+    pub position: Vec3,
+    pub tex_coord: Vec2,
+    /// RGBA8, read as a normalized `vec4` by the shader
+    pub color: [u8; 4],
+}
+
+impl ColoredVertex {
+    pub fn new(position: Vec3, tex_coord: Vec2, color: Color) -> ColoredVertex {
+        ColoredVertex {
+            position,
+            tex_coord,
+            color: color.to_rgba8(),
+        }
+    }
+}
+
+impl VertexFormat for ColoredVertex {
+    fn layout() -> VertexLayout {
+        vertex_layout!(ColoredVertex {
+            position => "a_position",
+            tex_coord => "a_tex_coord",
+            color => "a_color",
+        })
+    }
+}
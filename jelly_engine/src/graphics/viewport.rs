@@ -0,0 +1,86 @@
+use crate::math::prelude::Vec2;
+
+/// Rectangle of the screen, in pixels, where the virtual resolution is drawn
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Viewport {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Viewport {
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Viewport {
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Largest centered rectangle of `screen_size` keeping the aspect ratio of `virtual_size`
+    pub fn letterbox(screen_size: (u32, u32), virtual_size: (u32, u32)) -> Viewport {
+        let target_aspect_ratio = virtual_size.0 as f32 / virtual_size.1 as f32;
+
+        let width = screen_size.0 as i32;
+        let height = screen_size.1 as i32;
+
+        let mut calculated_height = (width as f32 / target_aspect_ratio) as i32;
+        let mut calculated_width = width;
+
+        if calculated_height > height {
+            calculated_height = height;
+            calculated_width = (calculated_height as f32 * target_aspect_ratio) as i32;
+        }
+
+        let vp_x = (width / 2) - (calculated_width / 2);
+        let vp_y = (height / 2) - (calculated_height / 2);
+
+        Viewport::new(vp_x, vp_y, calculated_width, calculated_height)
+    }
+
+    /// Largest centered whole multiple of `virtual_size` fitting in `screen_size`,
+    /// falls back to `letterbox` when the screen is smaller than the virtual size
+    pub fn integer_scale(screen_size: (u32, u32), virtual_size: (u32, u32)) -> Viewport {
+        let scale =
+            (screen_size.0 / virtual_size.0.max(1)).min(screen_size.1 / virtual_size.1.max(1));
+        if scale == 0 {
+            return Viewport::letterbox(screen_size, virtual_size);
+        }
+
+        let width = (virtual_size.0 * scale) as i32;
+        let height = (virtual_size.1 * scale) as i32;
+
+        Viewport::new(
+            (screen_size.0 as i32 - width) / 2,
+            (screen_size.1 as i32 - height) / 2,
+            width,
+            height,
+        )
+    }
+
+    /// Convert a screen position into virtual coordinates,
+    /// `None` when the position falls in the black bars
+    pub fn to_virtual(&self, position: (i32, i32), virtual_size: (u32, u32)) -> Option<Vec2> {
+        if self.width <= 0 || self.height <= 0 {
+            return None;
+        }
+
+        let x = (position.0 - self.x) as f32 * virtual_size.0 as f32 / self.width as f32;
+        let y = (position.1 - self.y) as f32 * virtual_size.1 as f32 / self.height as f32;
+
+        if x < 0.0 || y < 0.0 || x >= virtual_size.0 as f32 || y >= virtual_size.1 as f32 {
+            return None;
+        }
+
+        Some(Vec2::new(x, y))
+    }
+
+    pub fn apply(&self) {
+        unsafe {
+            gl::Viewport(self.x, self.y, self.width, self.height);
+            gl::Scissor(self.x, self.y, self.width, self.height);
+        }
+    }
+}
@@ -0,0 +1,379 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use sdl2::{
+    controller::{Axis, Button},
+    keyboard::Keycode,
+    mouse::MouseButton,
+};
+use serde::{Deserialize, Serialize};
+
+use super::input_state::Input;
+use crate::error::{JellyError, Result};
+use crate::math::prelude::Vec2;
+
+/// A digital input that can trigger an action
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(#[serde(with = "keycode_name")] Keycode),
+    Mouse(#[serde(with = "mouse_button_name")] MouseButton),
+    GamepadButton(#[serde(with = "button_name")] Button),
+}
+
+impl Binding {
+    /// First input pressed this frame, to let players pick a new binding
+    pub fn capture(input: &Input, gamepad: Option<u32>) -> Option<Binding> {
+        if let Some(keycode) = input.pressed_keys().next() {
+            return Some(Binding::Key(keycode));
+        }
+        if let Some(button) = input.pressed_mouse_buttons().next() {
+            return Some(Binding::Mouse(button));
+        }
+
+        gamepad
+            .and_then(|gamepad| input.gamepad(gamepad))
+            .and_then(|state| state.pressed_buttons().next())
+            .map(Binding::GamepadButton)
+    }
+
+    fn is_down(&self, input: &Input, gamepad: Option<u32>) -> bool {
+        match *self {
+            Binding::Key(keycode) => input.is_down(keycode),
+            Binding::Mouse(button) => input.is_mouse_down(button),
+            Binding::GamepadButton(button) => gamepad
+                .and_then(|gamepad| input.gamepad(gamepad))
+                .is_some_and(|state| state.is_down(button)),
+        }
+    }
+
+    fn just_pressed(&self, input: &Input, gamepad: Option<u32>) -> bool {
+        match *self {
+            Binding::Key(keycode) => input.just_pressed(keycode),
+            Binding::Mouse(button) => input.mouse_just_pressed(button),
+            Binding::GamepadButton(button) => gamepad
+                .and_then(|gamepad| input.gamepad(gamepad))
+                .is_some_and(|state| state.just_pressed(button)),
+        }
+    }
+
+    fn just_released(&self, input: &Input, gamepad: Option<u32>) -> bool {
+        match *self {
+            Binding::Key(keycode) => input.just_released(keycode),
+            Binding::Mouse(button) => input.mouse_just_released(button),
+            Binding::GamepadButton(button) => gamepad
+                .and_then(|gamepad| input.gamepad(gamepad))
+                .is_some_and(|state| state.just_released(button)),
+        }
+    }
+}
+
+/// An input producing a value in -1..1
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AxisBinding {
+    Keys {
+        #[serde(with = "keycode_name")]
+        negative: Keycode,
+        #[serde(with = "keycode_name")]
+        positive: Keycode,
+    },
+    GamepadAxis(#[serde(with = "axis_name")] Axis),
+}
+
+impl AxisBinding {
+    fn value(&self, input: &Input, gamepad: Option<u32>) -> f32 {
+        match *self {
+            AxisBinding::Keys { negative, positive } => {
+                let mut value = 0.0;
+                if input.is_down(negative) {
+                    value -= 1.0;
+                }
+                if input.is_down(positive) {
+                    value += 1.0;
+                }
+                value
+            }
+            AxisBinding::GamepadAxis(axis) => gamepad
+                .and_then(|gamepad| input.gamepad(gamepad))
+                .map_or(0.0, |state| state.axis(axis)),
+        }
+    }
+}
+
+/// Two axes read together, e.g. a stick or WASD
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Axis2dBinding {
+    pub x: Vec<AxisBinding>,
+    pub y: Vec<AxisBinding>,
+}
+
+/// Named actions and axes for one player.
+///
+/// Gamepad bindings read the gamepad in player slot `gamepad`, keyboard and
+/// mouse bindings are shared, so several profiles can split a single keyboard.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct BindingProfile {
+    #[serde(default)]
+    pub gamepad: Option<u32>,
+    #[serde(default)]
+    pub actions: HashMap<String, Vec<Binding>>,
+    #[serde(default)]
+    pub axes: HashMap<String, Vec<AxisBinding>>,
+    #[serde(default)]
+    pub axes_2d: HashMap<String, Axis2dBinding>,
+}
+
+impl BindingProfile {
+    pub fn new() -> BindingProfile {
+        BindingProfile::default()
+    }
+
+    pub fn bind(&mut self, action: &str, binding: Binding) {
+        let bindings = self.actions.entry(String::from(action)).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn unbind(&mut self, action: &str, binding: Binding) {
+        if let Some(bindings) = self.actions.get_mut(action) {
+            bindings.retain(|b| *b != binding);
+        }
+    }
+
+    /// Replace every binding of `action` with `binding`
+    pub fn rebind(&mut self, action: &str, binding: Binding) {
+        self.actions.insert(String::from(action), vec![binding]);
+    }
+
+    pub fn bind_axis(&mut self, axis: &str, binding: AxisBinding) {
+        let bindings = self.axes.entry(String::from(axis)).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn rebind_axis(&mut self, axis: &str, binding: AxisBinding) {
+        self.axes.insert(String::from(axis), vec![binding]);
+    }
+
+    pub fn bind_axis_2d(&mut self, axis: &str, binding: Axis2dBinding) {
+        self.axes_2d.insert(String::from(axis), binding);
+    }
+
+    pub fn is_down(&self, input: &Input, action: &str) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.is_down(input, self.gamepad))
+    }
+
+    pub fn just_pressed(&self, input: &Input, action: &str) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.just_pressed(input, self.gamepad))
+    }
+
+    pub fn just_released(&self, input: &Input, action: &str) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.just_released(input, self.gamepad))
+    }
+
+    /// Sum of the bound axes, clamped to -1..1. Unknown axes read 0
+    pub fn axis(&self, input: &Input, axis: &str) -> f32 {
+        match self.axes.get(axis) {
+            Some(bindings) => sum_axes(bindings, input, self.gamepad),
+            None => 0.0,
+        }
+    }
+
+    /// Bound 2D axis, clamped to the unit circle. Unknown axes read (0, 0)
+    pub fn axis_2d(&self, input: &Input, axis: &str) -> Vec2 {
+        match self.axes_2d.get(axis) {
+            Some(binding) => {
+                let x = sum_axes(&binding.x, input, self.gamepad);
+                let y = sum_axes(&binding.y, input, self.gamepad);
+
+                let length = (x * x + y * y).sqrt();
+                if length > 1.0 {
+                    Vec2::new(x / length, y / length)
+                } else {
+                    Vec2::new(x, y)
+                }
+            }
+            None => Vec2::zero(),
+        }
+    }
+
+    fn bindings(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map_or(&[], |b| b.as_slice())
+    }
+}
+
+fn sum_axes(bindings: &[AxisBinding], input: &Input, gamepad: Option<u32>) -> f32 {
+    bindings
+        .iter()
+        .map(|binding| binding.value(input, gamepad))
+        .sum::<f32>()
+        .clamp(-1.0, 1.0)
+}
+
+/// Set of named binding profiles, saved and loaded as RON
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct InputMap {
+    pub profiles: HashMap<String, BindingProfile>,
+}
+
+impl InputMap {
+    pub fn new() -> InputMap {
+        InputMap::default()
+    }
+
+    pub fn insert(&mut self, name: &str, profile: BindingProfile) {
+        self.profiles.insert(String::from(name), profile);
+    }
+
+    pub fn profile(&self, name: &str) -> Option<&BindingProfile> {
+        self.profiles.get(name)
+    }
+
+    pub fn profile_mut(&mut self, name: &str) -> Option<&mut BindingProfile> {
+        self.profiles.get_mut(name)
+    }
+
+    pub fn from_ron(source: &str) -> Result<InputMap> {
+        ron::from_str(source).map_err(|e| JellyError::InvalidBindings(e.to_string()))
+    }
+
+    pub fn to_ron(&self) -> Result<String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| JellyError::InvalidBindings(e.to_string()))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<InputMap> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(JellyError::AssetNotFound(path.to_path_buf()));
+        }
+
+        InputMap::from_ron(&fs::read_to_string(path)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_ron()?)?;
+
+        Ok(())
+    }
+}
+
+// SDL types are stored by their SDL name ("Left Shift", "a", "leftx"...)
+
+mod keycode_name {
+    use sdl2::keyboard::Keycode;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(keycode: &Keycode, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&keycode.name())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Keycode, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Keycode::from_name(&name).ok_or_else(|| D::Error::custom(format!("unknown key {}", name)))
+    }
+}
+
+mod mouse_button_name {
+    use sdl2::mouse::MouseButton;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        button: &MouseButton,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match button {
+            MouseButton::Left => "Left",
+            MouseButton::Middle => "Middle",
+            MouseButton::Right => "Right",
+            MouseButton::X1 => "X1",
+            MouseButton::X2 => "X2",
+            MouseButton::Unknown => "Unknown",
+        })
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<MouseButton, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        match name.as_str() {
+            "Left" => Ok(MouseButton::Left),
+            "Middle" => Ok(MouseButton::Middle),
+            "Right" => Ok(MouseButton::Right),
+            "X1" => Ok(MouseButton::X1),
+            "X2" => Ok(MouseButton::X2),
+            "Unknown" => Ok(MouseButton::Unknown),
+            _ => Err(D::Error::custom(format!("unknown mouse button {}", name))),
+        }
+    }
+}
+
+mod button_name {
+    use sdl2::controller::Button;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(button: &Button, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&button.string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Button, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Button::from_string(&name)
+            .ok_or_else(|| D::Error::custom(format!("unknown gamepad button {}", name)))
+    }
+}
+
+mod axis_name {
+    use sdl2::controller::Axis;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(axis: &Axis, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&axis.string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Axis, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Axis::from_string(&name)
+            .ok_or_else(|| D::Error::custom(format!("unknown gamepad axis {}", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mouse_bindings_round_trip() {
+        let mut profile = BindingProfile::new();
+        for button in [
+            MouseButton::Left,
+            MouseButton::Middle,
+            MouseButton::Right,
+            MouseButton::X1,
+            MouseButton::X2,
+            MouseButton::Unknown,
+        ] {
+            profile.bind("click", Binding::Mouse(button));
+        }
+        let mut map = InputMap::new();
+        map.insert("player", profile);
+
+        assert_eq!(InputMap::from_ron(&map.to_ron().unwrap()).unwrap(), map);
+    }
+
+    #[test]
+    fn unknown_mouse_button_names() {
+        let source = r#"(profiles: { "player": (actions: { "click": [Mouse("X3")] }) })"#;
+
+        assert!(matches!(
+            InputMap::from_ron(source),
+            Err(JellyError::InvalidBindings(_))
+        ));
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use sdl2::{
+    controller::{Axis, Button, GameController},
+    event::Event,
+    GameControllerSubsystem, JoystickSubsystem,
+};
+
+use super::input_state::Input;
+use crate::error::{JellyError, Result};
+
+const DEFAULT_STICK_DEADZONE: f32 = 0.2;
+const DEFAULT_TRIGGER_DEADZONE: f32 = 0.1;
+
+/// Values below these thresholds read as 0, values above are rescaled to 0..1
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Deadzone {
+    /// Radial deadzone applied to each stick as a whole
+    pub stick: f32,
+    pub trigger: f32,
+}
+
+impl Default for Deadzone {
+    fn default() -> Self {
+        Deadzone {
+            stick: DEFAULT_STICK_DEADZONE,
+            trigger: DEFAULT_TRIGGER_DEADZONE,
+        }
+    }
+}
+
+/// Buttons and axes of a single gamepad
+#[derive(Default)]
+pub struct GamepadState {
+    buttons_down: HashSet<Button>,
+    buttons_pressed: HashSet<Button>,
+    buttons_released: HashSet<Button>,
+    axes: HashMap<Axis, f32>,
+    deadzone: Deadzone,
+}
+
+impl GamepadState {
+    pub fn new(deadzone: Deadzone) -> GamepadState {
+        GamepadState {
+            deadzone,
+            ..GamepadState::default()
+        }
+    }
+
+    pub fn is_down(&self, button: Button) -> bool {
+        self.buttons_down.contains(&button)
+    }
+
+    pub fn just_pressed(&self, button: Button) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    pub fn just_released(&self, button: Button) -> bool {
+        self.buttons_released.contains(&button)
+    }
+
+    /// Buttons pressed this frame
+    pub fn pressed_buttons(&self) -> impl Iterator<Item = Button> + '_ {
+        self.buttons_pressed.iter().copied()
+    }
+
+    /// Axis value in -1..1 (0..1 for triggers) with the deadzone applied
+    pub fn axis(&self, axis: Axis) -> f32 {
+        let value = self.raw_axis(axis);
+
+        let other = match axis {
+            Axis::LeftX => Axis::LeftY,
+            Axis::LeftY => Axis::LeftX,
+            Axis::RightX => Axis::RightY,
+            Axis::RightY => Axis::RightX,
+            Axis::TriggerLeft | Axis::TriggerRight => {
+                return rescale(value, value.abs(), self.deadzone.trigger);
+            }
+        };
+
+        let other = self.raw_axis(other);
+        let magnitude = (value * value + other * other).sqrt();
+
+        rescale(value, magnitude, self.deadzone.stick)
+    }
+
+    /// Axis value as reported by SDL, without deadzone
+    pub fn raw_axis(&self, axis: Axis) -> f32 {
+        self.axes.get(&axis).copied().unwrap_or(0.0)
+    }
+
+    pub fn set_deadzone(&mut self, deadzone: Deadzone) {
+        self.deadzone = deadzone;
+    }
+
+    pub(crate) fn press(&mut self, button: Button) {
+        if self.buttons_down.insert(button) {
+            self.buttons_pressed.insert(button);
+        }
+    }
+
+    pub(crate) fn release(&mut self, button: Button) {
+        if self.buttons_down.remove(&button) {
+            self.buttons_released.insert(button);
+        }
+    }
+
+    pub(crate) fn set_axis(&mut self, axis: Axis, value: f32) {
+        self.axes.insert(axis, value.clamp(-1.0, 1.0));
+    }
+
+    pub(crate) fn end_frame(&mut self) {
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+    }
+}
+
+/// Scale `value` so that `magnitude` going from `deadzone` to 1 maps to 0..1
+fn rescale(value: f32, magnitude: f32, deadzone: f32) -> f32 {
+    if magnitude <= deadzone || magnitude == 0.0 {
+        return 0.0;
+    }
+
+    let scaled_magnitude = ((magnitude - deadzone) / (1.0 - deadzone)).min(1.0);
+
+    (value / magnitude * scaled_magnitude).clamp(-1.0, 1.0)
+}
+
+/// Opened SDL game controllers, keeps them alive while they're plugged in
+pub struct Gamepads {
+    subsystem: GameControllerSubsystem,
+    joystick: JoystickSubsystem,
+    controllers: HashMap<u32, GameController>,
+}
+
+impl Gamepads {
+    pub fn new(subsystem: GameControllerSubsystem, joystick: JoystickSubsystem) -> Gamepads {
+        Gamepads {
+            subsystem,
+            joystick,
+            controllers: HashMap::new(),
+        }
+    }
+
+    /// Add mappings from a gamecontrollerdb.txt file, returns how many were added
+    pub fn load_mappings<P: AsRef<Path>>(&self, path: P) -> Result<i32> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(JellyError::AssetNotFound(path.to_path_buf()));
+        }
+
+        self.subsystem
+            .load_mappings(path)
+            .map_err(|e| JellyError::Sdl(e.to_string()))
+    }
+
+    /// Open and close controllers as they're plugged in and out, and give them a player slot
+    pub fn handle_event(&mut self, event: &Event, input: &mut Input) {
+        match *event {
+            Event::ControllerDeviceAdded { which, .. } => match self.subsystem.open(which) {
+                Ok(controller) => {
+                    let instance_id = controller.instance_id();
+                    let guid = self
+                        .joystick
+                        .device_guid(which)
+                        .map(|guid| guid.string())
+                        .unwrap_or_default();
+
+                    let slot = input.connect_gamepad(instance_id, &guid);
+                    log::info!("Gamepad {} connected as player {}", controller.name(), slot);
+
+                    self.controllers.insert(instance_id, controller);
+                }
+                Err(e) => log::warn!("Could not open gamepad {}: {}", which, e),
+            },
+            Event::ControllerDeviceRemoved { which, .. } => {
+                if let Some(controller) = self.controllers.remove(&which) {
+                    log::info!("Gamepad {} disconnected", controller.name());
+                }
+                input.disconnect_gamepad(which);
+            }
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::{HashMap, HashSet};
+
+use sdl2::{
+    controller::{Axis, Button},
+    event::Event,
+    keyboard::Keycode,
+    mouse::MouseButton,
+};
+
+use super::gamepad::{Deadzone, GamepadState};
+use crate::graphics::prelude::Viewport;
+use crate::math::prelude::Vec2;
+
+struct GamepadSlot {
+    guid: String,
+    instance_id: Option<u32>,
+}
+
+/// Keyboard, mouse and gamepad state fed by the SDL event pump.
+///
+/// `just_pressed`/`just_released` stay true until the end of the first frame
+/// that ran at least one fixed update, so no transition is lost when a frame
+/// runs zero updates.
+#[derive(Default)]
+pub struct Input {
+    keys_down: HashSet<Keycode>,
+    keys_pressed: HashSet<Keycode>,
+    keys_released: HashSet<Keycode>,
+
+    buttons_down: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+
+    mouse_position: (i32, i32),
+    wheel_delta: (i32, i32),
+
+    /// Gamepads by player slot
+    gamepads: HashMap<u32, GamepadState>,
+    /// Slots ever assigned, with the GUID of their last gamepad and the
+    /// SDL instance id of the one currently plugged
+    slots: Vec<GamepadSlot>,
+    deadzone: Deadzone,
+
+    viewport: Viewport,
+    virtual_size: (u32, u32),
+}
+
+impl Input {
+    pub fn new() -> Input {
+        Input::default()
+    }
+
+    /// Track an SDL event, events not related to keyboard, mouse or gamepads are ignored
+    pub fn handle_event(&mut self, event: &Event) {
+        match *event {
+            Event::KeyDown {
+                keycode: Some(keycode),
+                repeat: false,
+                ..
+            } => self.press_key(keycode),
+            Event::KeyUp {
+                keycode: Some(keycode),
+                ..
+            } => self.release_key(keycode),
+            Event::MouseButtonDown {
+                mouse_btn, x, y, ..
+            } => {
+                self.mouse_position = (x, y);
+                if self.buttons_down.insert(mouse_btn) {
+                    self.buttons_pressed.insert(mouse_btn);
+                }
+            }
+            Event::MouseButtonUp {
+                mouse_btn, x, y, ..
+            } => {
+                self.mouse_position = (x, y);
+                if self.buttons_down.remove(&mouse_btn) {
+                    self.buttons_released.insert(mouse_btn);
+                }
+            }
+            Event::MouseMotion { x, y, .. } => self.mouse_position = (x, y),
+            Event::MouseWheel { x, y, .. } => {
+                self.wheel_delta.0 += x;
+                self.wheel_delta.1 += y;
+            }
+            Event::ControllerButtonDown { which, button, .. } => {
+                if let Some(slot) = self.gamepad_slot(which) {
+                    self.press_gamepad_button(slot, button);
+                }
+            }
+            Event::ControllerButtonUp { which, button, .. } => {
+                if let Some(slot) = self.gamepad_slot(which) {
+                    self.release_gamepad_button(slot, button);
+                }
+            }
+            Event::ControllerAxisMotion {
+                which, axis, value, ..
+            } => {
+                if let Some(slot) = self.gamepad_slot(which) {
+                    self.set_gamepad_axis(slot, axis, value as f32 / i16::MAX as f32);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    pub fn press_key(&mut self, keycode: Keycode) {
+        if self.keys_down.insert(keycode) {
+            self.keys_pressed.insert(keycode);
+        }
+    }
+
+    pub fn release_key(&mut self, keycode: Keycode) {
+        if self.keys_down.remove(&keycode) {
+            self.keys_released.insert(keycode);
+        }
+    }
+
+    /// Give a player slot to a newly plugged gamepad.
+    ///
+    /// A gamepad gets back the slot it had before being unplugged if it is
+    /// still free, otherwise the lowest free slot.
+    pub fn connect_gamepad(&mut self, instance_id: u32, guid: &str) -> u32 {
+        if let Some(slot) = self.gamepad_slot(instance_id) {
+            return slot;
+        }
+
+        let free_slot = |slot: &GamepadSlot| slot.instance_id.is_none();
+        let slot = match self
+            .slots
+            .iter()
+            .position(|slot| free_slot(slot) && slot.guid == guid)
+            .or_else(|| self.slots.iter().position(free_slot))
+        {
+            Some(slot) => slot,
+            None => {
+                self.slots.push(GamepadSlot {
+                    guid: String::new(),
+                    instance_id: None,
+                });
+                self.slots.len() - 1
+            }
+        };
+
+        self.slots[slot] = GamepadSlot {
+            guid: String::from(guid),
+            instance_id: Some(instance_id),
+        };
+
+        let slot = slot as u32;
+        self.gamepads.insert(slot, GamepadState::new(self.deadzone));
+
+        slot
+    }
+
+    /// Free the slot of an unplugged gamepad, it is kept for the same gamepad to come back
+    pub fn disconnect_gamepad(&mut self, instance_id: u32) {
+        if let Some(slot) = self.gamepad_slot(instance_id) {
+            self.slots[slot as usize].instance_id = None;
+            self.gamepads.remove(&slot);
+        }
+    }
+
+    /// Player slot of a plugged gamepad from its SDL instance id
+    pub fn gamepad_slot(&self, instance_id: u32) -> Option<u32> {
+        self.slots
+            .iter()
+            .position(|slot| slot.instance_id == Some(instance_id))
+            .map(|slot| slot as u32)
+    }
+
+    pub fn press_gamepad_button(&mut self, slot: u32, button: Button) {
+        if let Some(state) = self.gamepads.get_mut(&slot) {
+            state.press(button);
+        }
+    }
+
+    pub fn release_gamepad_button(&mut self, slot: u32, button: Button) {
+        if let Some(state) = self.gamepads.get_mut(&slot) {
+            state.release(button);
+        }
+    }
+
+    pub fn set_gamepad_axis(&mut self, slot: u32, axis: Axis, value: f32) {
+        if let Some(state) = self.gamepads.get_mut(&slot) {
+            state.set_axis(axis, value);
+        }
+    }
+
+    /// Deadzone used by every gamepad, current and future
+    pub fn set_deadzone(&mut self, deadzone: Deadzone) {
+        self.deadzone = deadzone;
+
+        for gamepad in self.gamepads.values_mut() {
+            gamepad.set_deadzone(deadzone);
+        }
+    }
+
+    /// Forget this frame's transitions and wheel motion, called by the engine
+    /// once the frame's updates have seen them
+    pub fn end_frame(&mut self) {
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+        self.wheel_delta = (0, 0);
+
+        for gamepad in self.gamepads.values_mut() {
+            gamepad.end_frame();
+        }
+    }
+
+    /// Letterbox rectangle in window coordinates, used to map the mouse to virtual coordinates
+    pub fn set_viewport(&mut self, viewport: Viewport, virtual_size: (u32, u32)) {
+        self.viewport = viewport;
+        self.virtual_size = virtual_size;
+    }
+
+    pub fn is_down(&self, keycode: Keycode) -> bool {
+        self.keys_down.contains(&keycode)
+    }
+
+    pub fn just_pressed(&self, keycode: Keycode) -> bool {
+        self.keys_pressed.contains(&keycode)
+    }
+
+    pub fn just_released(&self, keycode: Keycode) -> bool {
+        self.keys_released.contains(&keycode)
+    }
+
+    /// Keys pressed this frame
+    pub fn pressed_keys(&self) -> impl Iterator<Item = Keycode> + '_ {
+        self.keys_pressed.iter().copied()
+    }
+
+    /// Mouse buttons pressed this frame
+    pub fn pressed_mouse_buttons(&self) -> impl Iterator<Item = MouseButton> + '_ {
+        self.buttons_pressed.iter().copied()
+    }
+
+    pub fn is_mouse_down(&self, button: MouseButton) -> bool {
+        self.buttons_down.contains(&button)
+    }
+
+    pub fn mouse_just_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    pub fn mouse_just_released(&self, button: MouseButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+
+    /// Mouse position in window coordinates
+    pub fn mouse_position(&self) -> (i32, i32) {
+        self.mouse_position
+    }
+
+    /// Mouse position in virtual resolution coordinates, `None` over the black bars
+    pub fn mouse_virtual_position(&self) -> Option<Vec2> {
+        self.viewport
+            .to_virtual(self.mouse_position, self.virtual_size)
+    }
+
+    /// State of the gamepad in a player slot, `None` if none is plugged
+    pub fn gamepad(&self, slot: u32) -> Option<&GamepadState> {
+        self.gamepads.get(&slot)
+    }
+
+    /// Player slots with a gamepad plugged in
+    pub fn connected_gamepads(&self) -> impl Iterator<Item = u32> + '_ {
+        self.gamepads.keys().copied()
+    }
+
+    /// Wheel motion accumulated since the last frame, (horizontal, vertical)
+    pub fn wheel_delta(&self) -> (i32, i32) {
+        self.wheel_delta
+    }
+}
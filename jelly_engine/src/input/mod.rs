@@ -0,0 +1,12 @@
+mod actions;
+mod gamepad;
+mod input_state;
+
+pub mod prelude {
+    pub use crate::input::actions::*;
+    pub use crate::input::gamepad::*;
+    pub use crate::input::input_state::*;
+    pub use sdl2::controller::{Axis, Button};
+    pub use sdl2::keyboard::Keycode;
+    pub use sdl2::mouse::MouseButton;
+}
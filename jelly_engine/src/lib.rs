@@ -3,6 +3,14 @@
 
 mod assets;
 pub mod engine;
-mod gl_utilities;
-mod graphics;
-mod math;
+pub mod error;
+pub mod gl_utilities;
+pub mod graphics;
+pub mod input;
+pub mod logging;
+pub mod math;
+pub mod text;
+pub mod tilemap;
+
+pub use log;
+pub use sdl2;
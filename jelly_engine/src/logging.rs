@@ -0,0 +1,115 @@
+use std::sync::{Arc, Mutex};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::error::{JellyError, Result};
+
+/// A message kept by a capturing `Logger`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogRecord {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// Handle on the records kept by a capturing `Logger`
+#[derive(Clone, Default)]
+pub struct LogCapture {
+    records: Arc<Mutex<Vec<LogRecord>>>,
+}
+
+impl LogCapture {
+    pub fn records(&self) -> Vec<LogRecord> {
+        self.records.lock().unwrap().clone()
+    }
+
+    pub fn clear(&self) {
+        self.records.lock().unwrap().clear();
+    }
+}
+
+/// Logger backend for the `log` facade used across the engine.
+///
+/// Records are filtered by a default level, optionally overridden per target
+/// prefix (e.g. `jelly_engine::gl`), then printed to stderr or captured.
+pub struct Logger {
+    level: LevelFilter,
+    targets: Vec<(String, LevelFilter)>,
+    capture: Option<LogCapture>,
+}
+
+impl Logger {
+    pub fn new(level: LevelFilter) -> Logger {
+        Logger {
+            level,
+            targets: Vec::new(),
+            capture: None,
+        }
+    }
+
+    /// Override the level of every target starting with `target`
+    pub fn with_target(mut self, target: &str, level: LevelFilter) -> Logger {
+        self.targets.push((String::from(target), level));
+        // Longest prefixes first so the most specific override wins
+        self.targets
+            .sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        self
+    }
+
+    /// Keep records in memory instead of printing them
+    pub fn capture(mut self) -> (Logger, LogCapture) {
+        let capture = LogCapture::default();
+        self.capture = Some(capture.clone());
+
+        (self, capture)
+    }
+
+    /// Install as the global logger, can only be done once per process
+    pub fn init(self) -> Result<()> {
+        let max_level = self
+            .targets
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.level, std::cmp::max);
+
+        log::set_boxed_logger(Box::new(self)).map_err(|_| JellyError::LoggerAlreadySet)?;
+        log::set_max_level(max_level);
+
+        Ok(())
+    }
+
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .find(|(prefix, _)| target.starts_with(prefix.as_str()))
+            .map_or(self.level, |(_, level)| *level)
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        match &self.capture {
+            Some(capture) => capture.records.lock().unwrap().push(LogRecord {
+                level: record.level(),
+                target: String::from(record.target()),
+                message: record.args().to_string(),
+            }),
+            None => eprintln!(
+                "[{:<5} {}] {}",
+                record.level(),
+                record.target(),
+                record.args()
+            ),
+        }
+    }
+
+    fn flush(&self) {}
+}
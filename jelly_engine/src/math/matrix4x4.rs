@@ -216,4 +216,15 @@ impl Matrix4x4 {
 
         m
     }
+
+    /// Apply the matrix to a point (w = 1)
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        let m = &self.data;
+
+        Vec3::new(
+            m[0] * point.x + m[4] * point.y + m[8] * point.z + m[12],
+            m[1] * point.x + m[5] * point.y + m[9] * point.z + m[13],
+            m[2] * point.x + m[6] * point.y + m[10] * point.z + m[14],
+        )
+    }
 }
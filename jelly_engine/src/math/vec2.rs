@@ -1,6 +1,7 @@
-use std::ops::{Add, Mul};
+use std::ops::{Add, Mul, Sub};
 
 #[derive(Debug, Copy, Clone)]
+#[repr(C)]
 pub struct Vec2 {
     pub x: f32,
     pub y: f32,
@@ -29,6 +30,29 @@ impl Vec2 {
     pub fn scale(self, f: f32) -> Vec2 {
         Vec2::new(self.x * f, self.y * f)
     }
+
+    pub fn dot(self, other: Vec2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Same direction with a length of 1, the zero vector stays zero
+    pub fn normalized(self) -> Vec2 {
+        let length = self.length();
+        if length > 0.0 {
+            self.scale(1.0 / length)
+        } else {
+            self
+        }
+    }
+
+    /// Rotated a quarter turn, clockwise on screen since y points down
+    pub fn perpendicular(self) -> Vec2 {
+        Vec2::new(-self.y, self.x)
+    }
 }
 
 impl Add for Vec2 {
@@ -38,6 +62,13 @@ impl Add for Vec2 {
     }
 }
 
+impl Sub for Vec2 {
+    type Output = Vec2;
+    fn sub(self, other: Self) -> Self {
+        self.offset(-other.x, -other.y)
+    }
+}
+
 impl Mul for Vec2 {
     type Output = Vec2;
 
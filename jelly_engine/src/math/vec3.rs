@@ -1,6 +1,7 @@
 use std::ops::{Add, Mul};
 
 #[derive(Debug, Copy, Clone)]
+#[repr(C)]
 pub struct Vec3 {
     pub x: f32,
     pub y: f32,
@@ -0,0 +1,44 @@
+#version 410
+
+precision mediump float;
+
+uniform sampler2D u_texture;
+uniform vec2 u_resolution;
+
+uniform float u_threshold;
+uniform float u_intensity;
+// Blur radius, in pixels
+uniform float u_radius;
+
+in vec2 v_tex_coord;
+
+out vec4 fragColor;
+
+vec3 bright(vec2 uv)
+{
+    vec3 color = texture(u_texture, uv).rgb;
+    float luminance = dot(color, vec3(0.2126, 0.7152, 0.0722));
+
+    return color * step(u_threshold, luminance);
+}
+
+void main()
+{
+    vec4 color = texture(u_texture, v_tex_coord);
+    vec2 texel = u_radius / u_resolution;
+
+    // Single pass 5x5 gaussian of the bright parts
+    vec3 glow = vec3(0.0);
+    float total = 0.0;
+    for (int x = -2; x <= 2; x++) {
+        for (int y = -2; y <= 2; y++) {
+            float weight = exp(-float(x * x + y * y) / 4.0);
+            glow += bright(v_tex_coord + vec2(x, y) * texel * 0.5) * weight;
+            total += weight;
+        }
+    }
+
+    color.rgb += glow / total * u_intensity;
+
+    fragColor = color;
+}
@@ -0,0 +1,25 @@
+#version 410
+
+precision mediump float;
+
+uniform sampler2D u_texture;
+uniform vec2 u_resolution;
+
+// In pixels, at the edges of the screen
+uniform float u_offset;
+
+in vec2 v_tex_coord;
+
+out vec4 fragColor;
+
+void main()
+{
+    vec2 direction = (v_tex_coord - 0.5) * 2.0;
+    vec2 offset = direction * u_offset / u_resolution;
+
+    vec4 color = texture(u_texture, v_tex_coord);
+    color.r = texture(u_texture, v_tex_coord + offset).r;
+    color.b = texture(u_texture, v_tex_coord - offset).b;
+
+    fragColor = color;
+}
@@ -0,0 +1,38 @@
+#version 410
+
+precision mediump float;
+
+uniform sampler2D u_texture;
+
+// 256x16 strip of 16 slices: red across a slice, green down, blue across slices
+uniform sampler2D u_lut;
+uniform float u_intensity;
+
+in vec2 v_tex_coord;
+
+out vec4 fragColor;
+
+const float LUT_SIZE = 16.0;
+
+vec3 lookup(vec3 color)
+{
+    float blue = color.b * (LUT_SIZE - 1.0);
+    float slice0 = floor(blue);
+    float slice1 = min(slice0 + 1.0, LUT_SIZE - 1.0);
+
+    float x = (color.r * (LUT_SIZE - 1.0) + 0.5) / (LUT_SIZE * LUT_SIZE);
+    float y = (color.g * (LUT_SIZE - 1.0) + 0.5) / LUT_SIZE;
+
+    vec3 graded0 = texture(u_lut, vec2(slice0 / LUT_SIZE + x, y)).rgb;
+    vec3 graded1 = texture(u_lut, vec2(slice1 / LUT_SIZE + x, y)).rgb;
+
+    return mix(graded0, graded1, blue - slice0);
+}
+
+void main()
+{
+    vec4 color = texture(u_texture, v_tex_coord);
+    color.rgb = mix(color.rgb, lookup(clamp(color.rgb, 0.0, 1.0)), u_intensity);
+
+    fragColor = color;
+}
@@ -0,0 +1,34 @@
+#version 410
+
+precision mediump float;
+
+uniform sampler2D u_texture;
+uniform vec2 u_resolution;
+
+uniform float u_curvature;
+uniform float u_scanlines;
+
+in vec2 v_tex_coord;
+
+out vec4 fragColor;
+
+void main()
+{
+    // Barrel distortion around the center of the screen
+    vec2 centered = v_tex_coord * 2.0 - 1.0;
+    centered += centered * (centered.yx * centered.yx) * u_curvature;
+    vec2 uv = centered * 0.5 + 0.5;
+
+    if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        fragColor = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+
+    vec4 color = texture(u_texture, uv);
+
+    // One dark line between two rows of pixels
+    float scanline = 0.5 + 0.5 * cos(uv.y * u_resolution.y * 6.28318);
+    color.rgb *= mix(1.0, scanline, u_scanlines);
+
+    fragColor = color;
+}
@@ -0,0 +1,32 @@
+#version 410
+
+precision mediump float;
+
+uniform sampler2D u_texture;
+
+// Shades per color channel
+uniform float u_levels;
+
+in vec2 v_tex_coord;
+
+out vec4 fragColor;
+
+const float BAYER[16] = float[](
+     0.0,  8.0,  2.0, 10.0,
+    12.0,  4.0, 14.0,  6.0,
+     3.0, 11.0,  1.0,  9.0,
+    15.0,  7.0, 13.0,  5.0
+);
+
+void main()
+{
+    vec4 color = texture(u_texture, v_tex_coord);
+
+    ivec2 cell = ivec2(gl_FragCoord.xy) % 4;
+    float threshold = BAYER[cell.y * 4 + cell.x] / 16.0 - 0.5;
+
+    float steps = max(u_levels - 1.0, 1.0);
+    color.rgb = clamp(floor(color.rgb * steps + 0.5 + threshold) / steps, 0.0, 1.0);
+
+    fragColor = color;
+}
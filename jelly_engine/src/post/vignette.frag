@@ -0,0 +1,24 @@
+#version 410
+
+precision mediump float;
+
+uniform sampler2D u_texture;
+
+uniform float u_intensity;
+uniform float u_radius;
+uniform float u_softness;
+
+in vec2 v_tex_coord;
+
+out vec4 fragColor;
+
+void main()
+{
+    vec4 color = texture(u_texture, v_tex_coord);
+
+    float distance = length(v_tex_coord - 0.5) * 1.41421;
+    float vignette = smoothstep(u_radius, u_radius - u_softness, distance);
+    color.rgb *= mix(1.0, vignette, u_intensity);
+
+    fragColor = color;
+}
@@ -0,0 +1,187 @@
+// Import of AngelCode BMFont descriptors (https://www.angelcode.com/products/bmfont/).
+//
+// The text and XML formats are supported, both hold the same tags and
+// attributes. The binary format is refused.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::prelude::{Font, FontPage, Glyph};
+use crate::error::{JellyError, Result};
+use crate::math::prelude::Vec2;
+
+/// Tag of the descriptor with its attributes, e.g. `char id=65 x=2 ...`
+struct Entry {
+    tag: String,
+    attributes: HashMap<String, String>,
+}
+
+impl Entry {
+    fn get<T: FromStr>(&self, name: &str) -> Result<Option<T>> {
+        self.attributes
+            .get(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| invalid(format!("invalid {}.{}: {}", self.tag, name, value)))
+            })
+            .transpose()
+    }
+
+    fn required<T: FromStr>(&self, name: &str) -> Result<T> {
+        self.get(name)?
+            .ok_or_else(|| invalid(format!("missing {}.{}", self.tag, name)))
+    }
+}
+
+impl Font {
+    /// Read a BMFont descriptor, text or XML, page images are relative to `dir`
+    /// and left unloaded
+    pub fn from_bmfont(source: &str, dir: &Path) -> Result<Font> {
+        if source.starts_with("BMF") {
+            return Err(invalid(
+                "binary fonts are not supported, export as text or XML",
+            ));
+        }
+
+        let entries = if source.trim_start().starts_with('<') {
+            xml_entries(source)?
+        } else {
+            text_entries(source)?
+        };
+
+        let find = |tag: &str| entries.iter().find(|entry| entry.tag == tag);
+        let info = find("info").ok_or_else(|| invalid("missing info"))?;
+        let common = find("common").ok_or_else(|| invalid("missing common"))?;
+
+        let mut font = Font::new(
+            &info.get::<String>("face")?.unwrap_or_default(),
+            // Negative sizes mean the size matches the character height
+            info.get::<f32>("size")?.unwrap_or(0.0).abs(),
+            common.required("lineHeight")?,
+            common.required("base")?,
+        );
+
+        let mut pages = Vec::new();
+        for entry in entries.iter().filter(|entry| entry.tag == "page") {
+            let id: usize = entry.required("id")?;
+            let file: String = entry.required("file")?;
+            if pages.len() <= id {
+                pages.resize(id + 1, None);
+            }
+            pages[id] = Some(dir.join(file));
+        }
+        font.pages = pages
+            .into_iter()
+            .map(|image| FontPage {
+                image,
+                texture: None,
+            })
+            .collect();
+
+        for entry in entries.iter().filter(|entry| entry.tag == "char") {
+            let id: i64 = entry.required("id")?;
+            let character = match u32::try_from(id).ok().and_then(char::from_u32) {
+                Some(character) => character,
+                // Id -1 is the glyph for missing characters in some exporters
+                None => continue,
+            };
+
+            font.add_glyph(
+                character,
+                Glyph {
+                    page: entry.get("page")?.unwrap_or(0),
+                    x: entry.required("x")?,
+                    y: entry.required("y")?,
+                    width: entry.required("width")?,
+                    height: entry.required("height")?,
+                    offset: Vec2::new(
+                        entry.get("xoffset")?.unwrap_or(0.0),
+                        entry.get("yoffset")?.unwrap_or(0.0),
+                    ),
+                    advance: entry.get("xadvance")?.unwrap_or(0.0),
+                },
+            );
+        }
+
+        for entry in entries.iter().filter(|entry| entry.tag == "kerning") {
+            let first = char::from_u32(entry.required("first")?);
+            let second = char::from_u32(entry.required("second")?);
+            if let (Some(first), Some(second)) = (first, second) {
+                font.set_kerning(first, second, entry.required("amount")?);
+            }
+        }
+
+        Ok(font)
+    }
+}
+
+/// One tag per line, followed by `key=value` pairs, values may be quoted
+fn text_entries(source: &str) -> Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+
+    for line in source.lines() {
+        let mut rest = line.trim();
+        let tag = match rest.split_whitespace().next() {
+            Some(tag) => tag.to_string(),
+            None => continue,
+        };
+        rest = rest[tag.len()..].trim_start();
+
+        let mut attributes = HashMap::new();
+        while !rest.is_empty() {
+            let equal = rest
+                .find('=')
+                .ok_or_else(|| invalid(format!("expected key=value in {}", line)))?;
+            let key = rest[..equal].trim().to_string();
+            rest = &rest[equal + 1..];
+
+            let value = if let Some(quoted) = rest.strip_prefix('"') {
+                let end = quoted
+                    .find('"')
+                    .ok_or_else(|| invalid(format!("unterminated string in {}", line)))?;
+                rest = &quoted[end + 1..];
+                &quoted[..end]
+            } else {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let value = &rest[..end];
+                rest = &rest[end..];
+                value
+            };
+
+            attributes.insert(key, value.to_string());
+            rest = rest.trim_start();
+        }
+
+        entries.push(Entry { tag, attributes });
+    }
+
+    Ok(entries)
+}
+
+/// Every element below `<font>`, nested ones like `<pages><page/></pages>` included
+fn xml_entries(source: &str) -> Result<Vec<Entry>> {
+    let document = roxmltree::Document::parse(source).map_err(|e| invalid(e.to_string()))?;
+    let root = document.root_element();
+    if root.tag_name().name() != "font" {
+        return Err(invalid("missing font element"));
+    }
+
+    Ok(root
+        .descendants()
+        .filter(|node| node.is_element() && *node != root)
+        .map(|node| Entry {
+            tag: node.tag_name().name().to_string(),
+            attributes: node
+                .attributes()
+                .map(|attribute| (attribute.name().to_string(), attribute.value().to_string()))
+                .collect(),
+        })
+        .collect())
+}
+
+fn invalid<S: Into<String>>(message: S) -> JellyError {
+    JellyError::InvalidFont(message.into())
+}
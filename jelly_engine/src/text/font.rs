@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::{JellyError, Result};
+use crate::graphics::prelude::{Color, Quad, SpriteBatch, Texture, UvRect};
+use crate::math::prelude::{Matrix4x4, Vec2, Vec3};
+
+/// Character of a font, packed in one of its pages
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Glyph {
+    pub page: usize,
+    /// Rectangle in the page, in pixels
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// From the pen position on the top of the line to the top left corner of the glyph
+    pub offset: Vec2,
+    /// Pen move after the glyph
+    pub advance: f32,
+}
+
+/// Texture holding some of the glyphs of a font
+#[derive(Debug, Clone)]
+pub struct FontPage {
+    pub image: Option<PathBuf>,
+    pub texture: Option<Texture>,
+}
+
+/// Glyph placed by `Font::layout`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PlacedGlyph {
+    pub character: char,
+    pub glyph: Glyph,
+    /// Top left corner, relative to the top left corner of the text
+    pub position: Vec2,
+}
+
+/// Bitmap font, drawn through the `SpriteBatch`
+#[derive(Debug, Clone)]
+pub struct Font {
+    pub name: String,
+    /// Em size the font was rendered at, in pixels
+    pub size: f32,
+    /// Distance between two lines
+    pub line_height: f32,
+    /// From the top of a line to its baseline
+    pub base: f32,
+    pub pages: Vec<FontPage>,
+    /// Round glyph positions to whole pixels, keeps small text crisp
+    pub pixel_snap: bool,
+    glyphs: HashMap<char, Glyph>,
+    kerning: HashMap<(char, char), f32>,
+}
+
+impl Font {
+    pub fn new(name: &str, size: f32, line_height: f32, base: f32) -> Font {
+        Font {
+            name: String::from(name),
+            size,
+            line_height,
+            base,
+            pages: Vec::new(),
+            pixel_snap: false,
+            glyphs: HashMap::new(),
+            kerning: HashMap::new(),
+        }
+    }
+
+    pub fn add_glyph(&mut self, character: char, glyph: Glyph) {
+        self.glyphs.insert(character, glyph);
+    }
+
+    pub fn glyph(&self, character: char) -> Option<&Glyph> {
+        self.glyphs.get(&character)
+    }
+
+    /// Glyph of `character`, or of `?` when the font doesn't have it
+    pub(super) fn glyph_or_fallback(&self, character: char) -> Option<(char, &Glyph)> {
+        match self.glyph(character) {
+            Some(glyph) => Some((character, glyph)),
+            None => self.glyph('?').map(|glyph| ('?', glyph)),
+        }
+    }
+
+    /// Pen adjustment between `first` and `second` when they follow each other
+    pub fn set_kerning(&mut self, first: char, second: char, amount: f32) {
+        self.kerning.insert((first, second), amount);
+    }
+
+    pub fn kerning(&self, first: char, second: char) -> f32 {
+        self.kerning.get(&(first, second)).copied().unwrap_or(0.0)
+    }
+
+    /// Read an AngelCode BMFont file, in the text or XML format, and load its pages
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Font> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(JellyError::AssetNotFound(path.to_path_buf()));
+        }
+
+        let source = std::fs::read_to_string(path)?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+        let mut font = Font::from_bmfont(&source, dir)?;
+        font.load_textures()?;
+
+        Ok(font)
+    }
+
+    /// Load the image of every page still without texture
+    pub fn load_textures(&mut self) -> Result<()> {
+        for page in &mut self.pages {
+            if let (None, Some(image)) = (&page.texture, &page.image) {
+                page.texture = Some(Texture::from_file(image)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Place the glyphs of `text`, lines are split on `\n`. Characters
+    /// missing from the font are replaced by `?`, or skipped without it
+    pub fn layout(&self, text: &str) -> Vec<PlacedGlyph> {
+        self.place(text).0
+    }
+
+    /// Size of `text` once laid out, lines are `line_height` high
+    pub fn measure(&self, text: &str) -> Vec2 {
+        self.place(text).1
+    }
+
+    fn place(&self, text: &str) -> (Vec<PlacedGlyph>, Vec2) {
+        let mut placed = Vec::with_capacity(text.len());
+        let mut pen = Vec2::zero();
+        let mut width: f32 = 0.0;
+        let mut previous = None;
+
+        for character in text.chars() {
+            if character == '\n' {
+                pen = Vec2::new(0.0, pen.y + self.line_height);
+                previous = None;
+                continue;
+            }
+
+            let (character, glyph) = match self.glyph_or_fallback(character) {
+                Some(found) => found,
+                None => continue,
+            };
+
+            if let Some(previous) = previous {
+                pen.x += self.kerning(previous, character);
+            }
+
+            let mut position = pen + glyph.offset;
+            if self.pixel_snap {
+                position = Vec2::new(position.x.round(), position.y.round());
+            }
+
+            placed.push(PlacedGlyph {
+                character,
+                glyph: *glyph,
+                position,
+            });
+
+            pen.x += glyph.advance;
+            width = width.max(pen.x);
+            previous = Some(character);
+        }
+
+        (placed, Vec2::new(width, pen.y + self.line_height))
+    }
+
+    /// Queue `text` with its top left corner placed by `model`
+    pub fn draw(&self, batch: &mut SpriteBatch, text: &str, model: &Matrix4x4, tint: Color) {
+        for placed in self.layout(text) {
+            self.draw_glyph(batch, &placed.glyph, placed.position, model, tint);
+        }
+    }
+
+    /// Queue `text` with its top left corner at `position`, rounded with `pixel_snap`
+    pub fn draw_at(&self, batch: &mut SpriteBatch, text: &str, mut position: Vec2, tint: Color) {
+        if self.pixel_snap {
+            position = Vec2::new(position.x.round(), position.y.round());
+        }
+
+        let model = Matrix4x4::translation(Vec3::new(position.x, position.y, 0.0));
+        self.draw(batch, text, &model, tint);
+    }
+
+    /// Queue `glyph` with its top left corner at `position`, in the space of `model`
+    pub(super) fn draw_glyph(
+        &self,
+        batch: &mut SpriteBatch,
+        glyph: &Glyph,
+        position: Vec2,
+        model: &Matrix4x4,
+        tint: Color,
+    ) {
+        let texture = match self.pages.get(glyph.page).and_then(|page| page.texture) {
+            Some(texture) => texture,
+            None => return,
+        };
+        if glyph.width == 0 || glyph.height == 0 {
+            return;
+        }
+
+        let texture_width = texture.width().max(1) as f32;
+        let texture_height = texture.height().max(1) as f32;
+        let quad = Quad {
+            uv: UvRect::new(
+                glyph.x as f32 / texture_width,
+                glyph.y as f32 / texture_height,
+                (glyph.x + glyph.width) as f32 / texture_width,
+                (glyph.y + glyph.height) as f32 / texture_height,
+            ),
+            tint,
+            ..Quad::new(glyph.width as f32, glyph.height as f32)
+        };
+
+        let position = Vec3::new(position.x, position.y, 0.0);
+        batch.draw(&texture, &quad, &(model * Matrix4x4::translation(position)));
+    }
+}
@@ -0,0 +1,652 @@
+use super::prelude::{Font, Glyph, GlyphStyle, StyledText};
+use crate::graphics::prelude::{Color, SpriteBatch};
+use crate::math::prelude::{Matrix4x4, Vec2};
+
+/// Radians per second
+const WAVE_SPEED: f32 = 6.0;
+/// Phase between two characters, in radians
+const WAVE_STEP: f32 = 0.6;
+/// Relative to the line height
+const WAVE_HEIGHT: f32 = 0.15;
+/// New offsets per second
+const SHAKE_RATE: f32 = 20.0;
+/// Relative to the line height
+const SHAKE_AMOUNT: f32 = 0.08;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum HorizontalAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+    /// Wrapped lines are stretched to the max width, the last line of each
+    /// paragraph stays on the left
+    Justify,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum VerticalAlign {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// Where lines longer than the max width are broken
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum WrapMode {
+    None,
+    /// Between words, words longer than a line are broken between characters
+    #[default]
+    Word,
+    /// Between any two characters
+    Character,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LayoutOptions {
+    /// Width lines are wrapped to and aligned in, `None` aligns them in the widest one
+    pub max_width: Option<f32>,
+    pub wrap: WrapMode,
+    pub align: HorizontalAlign,
+    /// Height of the box the text is aligned in, `None` keeps it at the top
+    pub box_height: Option<f32>,
+    pub vertical_align: VerticalAlign,
+    /// Multiplies the line height of the font
+    pub line_spacing: f32,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        LayoutOptions {
+            max_width: None,
+            wrap: WrapMode::default(),
+            align: HorizontalAlign::default(),
+            box_height: None,
+            vertical_align: VerticalAlign::default(),
+            line_spacing: 1.0,
+        }
+    }
+}
+
+/// Glyph placed by a `TextLayout`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LayoutGlyph {
+    pub character: char,
+    pub glyph: Glyph,
+    /// Top left corner, relative to the top left corner of the box
+    pub position: Vec2,
+    pub style: GlyphStyle,
+    /// Position of the character in the text without markup, e.g. to reveal it progressively
+    pub index: usize,
+    pub line: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TextLine {
+    /// Left end of the line once aligned
+    pub x: f32,
+    /// Top of the line
+    pub y: f32,
+    /// Trailing spaces excluded
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Styled text wrapped and aligned in a box, computed without touching GL
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TextLayout {
+    pub glyphs: Vec<LayoutGlyph>,
+    pub lines: Vec<TextLine>,
+}
+
+/// Character on its way to a line
+#[derive(Copy, Clone)]
+struct Item {
+    character: char,
+    glyph: Glyph,
+    style: GlyphStyle,
+    index: usize,
+}
+
+/// Line with the pen position of its items
+struct PlacedLine {
+    items: Vec<(Item, f32)>,
+    width: f32,
+    wrapped: bool,
+}
+
+impl Item {
+    fn is_space(&self) -> bool {
+        self.character.is_whitespace()
+    }
+}
+
+impl TextLayout {
+    pub fn new(font: &Font, text: &StyledText, options: &LayoutOptions) -> TextLayout {
+        let mut paragraphs = vec![Vec::new()];
+        for (index, (character, style)) in text.chars().enumerate() {
+            if character == '\n' {
+                paragraphs.push(Vec::new());
+                continue;
+            }
+
+            if let Some((character, glyph)) = font.glyph_or_fallback(character) {
+                paragraphs.last_mut().unwrap().push(Item {
+                    character,
+                    glyph: *glyph,
+                    style,
+                    index,
+                });
+            }
+        }
+
+        // Items of each line, and whether it was wrapped
+        let mut lines: Vec<(Vec<Item>, bool)> = Vec::new();
+        for paragraph in paragraphs {
+            wrap(font, paragraph, options, &mut lines);
+        }
+
+        let line_height = font.line_height * options.line_spacing;
+        let text_height = lines.len() as f32 * line_height;
+        let top = match (options.box_height, options.vertical_align) {
+            (None, _) | (_, VerticalAlign::Top) => 0.0,
+            (Some(height), VerticalAlign::Middle) => (height - text_height) / 2.0,
+            (Some(height), VerticalAlign::Bottom) => height - text_height,
+        };
+
+        let placed: Vec<PlacedLine> = lines
+            .into_iter()
+            .map(|(items, wrapped)| {
+                let (items, width) = place(font, items);
+                PlacedLine {
+                    items,
+                    width,
+                    wrapped,
+                }
+            })
+            .collect();
+        let box_width = options
+            .max_width
+            .unwrap_or_else(|| placed.iter().map(|line| line.width).fold(0.0, f32::max));
+
+        let mut layout = TextLayout::default();
+        for (line_index, line) in placed.into_iter().enumerate() {
+            let PlacedLine {
+                items,
+                width,
+                wrapped,
+            } = line;
+            let y = top + line_index as f32 * line_height;
+            let (x, width, gap) = match options.align {
+                HorizontalAlign::Left => (0.0, width, 0.0),
+                HorizontalAlign::Center => ((box_width - width) / 2.0, width, 0.0),
+                HorizontalAlign::Right => (box_width - width, width, 0.0),
+                HorizontalAlign::Justify => match inner_spaces(&items) {
+                    spaces if wrapped && spaces > 0 && width < box_width => {
+                        (0.0, box_width, (box_width - width) / spaces as f32)
+                    }
+                    _ => (0.0, width, 0.0),
+                },
+            };
+
+            let mut stretch = 0.0;
+            let mut after_word = false;
+            for (item, pen) in items {
+                let mut position = Vec2::new(x + pen + stretch, y) + item.glyph.offset;
+                if font.pixel_snap {
+                    position = Vec2::new(position.x.round(), position.y.round());
+                }
+                if item.is_space() && after_word {
+                    stretch += gap;
+                }
+                after_word |= !item.is_space();
+
+                layout.glyphs.push(LayoutGlyph {
+                    character: item.character,
+                    glyph: item.glyph,
+                    position,
+                    style: item.style,
+                    index: item.index,
+                    line: line_index,
+                });
+            }
+
+            layout.lines.push(TextLine {
+                x,
+                y,
+                width,
+                height: line_height,
+            });
+        }
+
+        layout
+    }
+
+    /// Top left corner and size of the rectangle holding every line
+    pub fn bounds(&self) -> (Vec2, Vec2) {
+        let (first, last) = match (self.lines.first(), self.lines.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return (Vec2::zero(), Vec2::zero()),
+        };
+
+        let left = self
+            .lines
+            .iter()
+            .map(|line| line.x)
+            .fold(f32::MAX, f32::min);
+        let right = self
+            .lines
+            .iter()
+            .map(|line| line.x + line.width)
+            .fold(f32::MIN, f32::max);
+        (
+            Vec2::new(left, first.y),
+            Vec2::new(right - left, last.y + last.height - first.y),
+        )
+    }
+
+    /// Queue the glyphs in the space of `model`, `time` in seconds drives the
+    /// wave and shake effects. Colored runs keep the alpha of `tint`
+    pub fn draw(
+        &self,
+        font: &Font,
+        batch: &mut SpriteBatch,
+        model: &Matrix4x4,
+        tint: Color,
+        time: f32,
+    ) {
+        for glyph in &self.glyphs {
+            let mut position = glyph.position;
+            if glyph.style.wave {
+                let phase = time * WAVE_SPEED - glyph.index as f32 * WAVE_STEP;
+                position.y += phase.sin() * WAVE_HEIGHT * font.line_height;
+            }
+            if glyph.style.shake {
+                let tick = (time * SHAKE_RATE).floor() as u32;
+                let amount = SHAKE_AMOUNT * font.line_height;
+                position.x += noise(glyph.index, tick, 0) * amount;
+                position.y += noise(glyph.index, tick, 1) * amount;
+            }
+            if font.pixel_snap {
+                position = Vec2::new(position.x.round(), position.y.round());
+            }
+
+            let color = match glyph.style.color {
+                Some(color) => Color {
+                    a: color.a * tint.a,
+                    ..color
+                },
+                None => tint,
+            };
+
+            font.draw_glyph(batch, &glyph.glyph, position, model, color);
+        }
+    }
+}
+
+/// Split a paragraph in lines no wider than `options.max_width`
+fn wrap(
+    font: &Font,
+    items: Vec<Item>,
+    options: &LayoutOptions,
+    lines: &mut Vec<(Vec<Item>, bool)>,
+) {
+    let max_width = match (options.max_width, options.wrap) {
+        (Some(max_width), WrapMode::Word) | (Some(max_width), WrapMode::Character) => max_width,
+        _ => {
+            lines.push((items, false));
+            return;
+        }
+    };
+
+    // Runs of spaces and of other characters, broken between characters
+    // where words can't be kept whole
+    let mut runs: Vec<Vec<Item>> = Vec::new();
+    for item in items {
+        let joins = runs.last().and_then(|run| run.last()).is_some_and(|last| {
+            last.is_space() == item.is_space()
+                && (item.is_space() || options.wrap == WrapMode::Word)
+        });
+        if joins {
+            runs.last_mut().unwrap().push(item);
+        } else {
+            runs.push(vec![item]);
+        }
+    }
+
+    let mut line: Vec<Item> = Vec::new();
+    // Pen position after the last item of the line, kerning included
+    let mut pen = 0.0;
+    let mut has_word = false;
+    let step = |previous: Option<&Item>, item: &Item| {
+        previous.map_or(0.0, |previous| {
+            font.kerning(previous.character, item.character)
+        }) + item.glyph.advance
+    };
+
+    for run in runs {
+        if run[0].is_space() {
+            // Spaces left at the start of a wrapped line are dropped
+            if !line.is_empty() || lines.last().is_none_or(|(_, wrapped)| !wrapped) {
+                for item in run {
+                    pen += step(line.last(), &item);
+                    line.push(item);
+                }
+            }
+            continue;
+        }
+
+        // Keep the word whole on the next line when it doesn't fit on this one
+        let word: f32 = run
+            .iter()
+            .enumerate()
+            .map(|(index, item)| step(index.checked_sub(1).map(|index| &run[index]), item))
+            .sum();
+        let kerning = line
+            .last()
+            .map_or(0.0, |last| font.kerning(last.character, run[0].character));
+        if has_word && pen + kerning + word > max_width {
+            lines.push((trimmed(std::mem::take(&mut line)), true));
+            pen = 0.0;
+            has_word = false;
+        }
+
+        for item in run {
+            let mut next = pen + step(line.last(), &item);
+            if has_word && next > max_width {
+                lines.push((trimmed(std::mem::take(&mut line)), true));
+                next = step(None, &item);
+            }
+
+            pen = next;
+            has_word = true;
+            line.push(item);
+        }
+    }
+
+    lines.push((trimmed(line), false));
+}
+
+/// Pen position of each item along a line, and the width of the line
+/// without its trailing spaces
+fn place(font: &Font, items: Vec<Item>) -> (Vec<(Item, f32)>, f32) {
+    let mut pen = 0.0;
+    let mut width = 0.0;
+    let mut previous: Option<char> = None;
+
+    let placed = items
+        .into_iter()
+        .map(|item| {
+            if let Some(previous) = previous {
+                pen += font.kerning(previous, item.character);
+            }
+            let position = pen;
+
+            pen += item.glyph.advance;
+            if !item.is_space() {
+                width = pen;
+            }
+            previous = Some(item.character);
+
+            (item, position)
+        })
+        .collect();
+
+    (placed, width)
+}
+
+fn trimmed(mut items: Vec<Item>) -> Vec<Item> {
+    while items.last().is_some_and(Item::is_space) {
+        items.pop();
+    }
+
+    items
+}
+
+/// Spaces between the first and last words of a line
+fn inner_spaces(items: &[(Item, f32)]) -> usize {
+    let first = items.iter().position(|(item, _)| !item.is_space());
+    let last = items.iter().rposition(|(item, _)| !item.is_space());
+
+    match (first, last) {
+        (Some(first), Some(last)) => items[first..last]
+            .iter()
+            .filter(|(item, _)| item.is_space())
+            .count(),
+        _ => 0,
+    }
+}
+
+/// Hash of a character and time step, in -1..1
+fn noise(index: usize, tick: u32, axis: u32) -> f32 {
+    let mut hash = (index as u32).wrapping_mul(0x9E37_79B1)
+        ^ tick.wrapping_mul(0x85EB_CA77)
+        ^ axis.wrapping_mul(0xC2B2_AE3D);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2C1B_3C6D);
+    hash ^= hash >> 12;
+
+    hash as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADVANCE: f32 = 10.0;
+    const LINE_HEIGHT: f32 = 12.0;
+
+    /// Monospace font without textures, `av` kerned by -2
+    fn font() -> Font {
+        let mut font = Font::new("test", 10.0, LINE_HEIGHT, 8.0);
+        for character in ('a'..='z').chain(" ?".chars()) {
+            font.add_glyph(
+                character,
+                Glyph {
+                    page: 0,
+                    x: 0,
+                    y: 0,
+                    width: 8,
+                    height: 10,
+                    offset: Vec2::zero(),
+                    advance: ADVANCE,
+                },
+            );
+        }
+        font.set_kerning('a', 'v', -2.0);
+
+        font
+    }
+
+    fn layout(text: &str, options: LayoutOptions) -> TextLayout {
+        TextLayout::new(&font(), &StyledText::plain(text), &options)
+    }
+
+    fn wrapped(max_width: f32, wrap: WrapMode, align: HorizontalAlign) -> LayoutOptions {
+        LayoutOptions {
+            max_width: Some(max_width),
+            wrap,
+            align,
+            ..LayoutOptions::default()
+        }
+    }
+
+    /// Text of each line
+    fn lines(layout: &TextLayout) -> Vec<String> {
+        let mut lines = vec![String::new(); layout.lines.len()];
+        for glyph in &layout.glyphs {
+            lines[glyph.line].push(glyph.character);
+        }
+
+        lines
+    }
+
+    fn x_positions(layout: &TextLayout, line: usize) -> Vec<f32> {
+        layout
+            .glyphs
+            .iter()
+            .filter(|glyph| glyph.line == line)
+            .map(|glyph| glyph.position.x)
+            .collect()
+    }
+
+    #[test]
+    fn word_wrap() {
+        let options = wrapped(75.0, WrapMode::Word, HorizontalAlign::Left);
+        let layout = layout("aaa bbb ccc", options);
+
+        assert_eq!(lines(&layout), ["aaa bbb", "ccc"]);
+        assert_eq!(layout.lines[0].width, 70.0);
+        assert_eq!(layout.lines[1].y, LINE_HEIGHT);
+        // The space the line was wrapped at is dropped
+        assert_eq!(layout.glyphs[7].index, 8);
+    }
+
+    #[test]
+    fn long_words_are_broken() {
+        let options = wrapped(45.0, WrapMode::Word, HorizontalAlign::Left);
+
+        assert_eq!(
+            lines(&layout("aaaaaaaaaa", options)),
+            ["aaaa", "aaaa", "aa"]
+        );
+        // Moved to its own line before being broken
+        assert_eq!(
+            lines(&layout("bb aaaaaaaa", options)),
+            ["bb", "aaaa", "aaaa"]
+        );
+    }
+
+    #[test]
+    fn character_wrap() {
+        let options = wrapped(55.0, WrapMode::Character, HorizontalAlign::Left);
+
+        assert_eq!(lines(&layout("aaa bbb", options)), ["aaa b", "bb"]);
+    }
+
+    #[test]
+    fn no_wrap() {
+        let options = wrapped(20.0, WrapMode::None, HorizontalAlign::Left);
+
+        assert_eq!(lines(&layout("aaa bbb", options)), ["aaa bbb"]);
+    }
+
+    #[test]
+    fn wrap_counts_kerning() {
+        // 38 pixels wide with the kerning, 40 without
+        let options = wrapped(38.0, WrapMode::Word, HorizontalAlign::Left);
+
+        assert_eq!(lines(&layout("aaav", options)), ["aaav"]);
+        assert_eq!(lines(&layout("aaavv", options)), ["aaav", "v"]);
+    }
+
+    #[test]
+    fn spaces_are_dropped_around_wraps() {
+        let options = wrapped(45.0, WrapMode::Word, HorizontalAlign::Left);
+        let layout = layout("aaa    bbb", options);
+
+        assert_eq!(lines(&layout), ["aaa", "bbb"]);
+        assert_eq!(layout.lines[0].width, 30.0);
+        assert_eq!(x_positions(&layout, 1), [0.0, 10.0, 20.0]);
+        // The dropped spaces still count in the indices
+        assert_eq!(layout.glyphs[3].index, 7);
+    }
+
+    #[test]
+    fn paragraphs() {
+        let layout = layout("aa\n\nbb", LayoutOptions::default());
+
+        assert_eq!(lines(&layout), ["aa", "", "bb"]);
+        assert_eq!(layout.glyphs[2].index, 4);
+        assert_eq!(layout.glyphs[2].position.y, 2.0 * LINE_HEIGHT);
+    }
+
+    #[test]
+    fn center_and_right_alignment() {
+        let center = layout(
+            "aa bbbb",
+            wrapped(50.0, WrapMode::Word, HorizontalAlign::Center),
+        );
+        assert_eq!(lines(&center), ["aa", "bbbb"]);
+        assert_eq!((center.lines[0].x, center.lines[1].x), (15.0, 5.0));
+        assert_eq!(x_positions(&center, 0), [15.0, 25.0]);
+
+        let right = layout(
+            "aa bbbb",
+            wrapped(50.0, WrapMode::Word, HorizontalAlign::Right),
+        );
+        assert_eq!((right.lines[0].x, right.lines[1].x), (30.0, 10.0));
+
+        // Without a max width, lines align in the widest one
+        let options = LayoutOptions {
+            align: HorizontalAlign::Right,
+            ..LayoutOptions::default()
+        };
+        let unbounded = layout("aa\nbbbb", options);
+        assert_eq!((unbounded.lines[0].x, unbounded.lines[1].x), (20.0, 0.0));
+    }
+
+    #[test]
+    fn justify() {
+        let layout = layout(
+            "aa b cc dddd",
+            wrapped(75.0, WrapMode::Word, HorizontalAlign::Justify),
+        );
+
+        assert_eq!(lines(&layout), ["aa b cc", "dddd"]);
+        // 5 pixels shared by the two spaces
+        assert_eq!(
+            x_positions(&layout, 0),
+            [0.0, 10.0, 20.0, 32.5, 42.5, 55.0, 65.0]
+        );
+        assert_eq!(layout.lines[0].width, 75.0);
+
+        // The last line of the paragraph isn't stretched
+        assert_eq!(x_positions(&layout, 1), [0.0, 10.0, 20.0, 30.0]);
+        assert_eq!((layout.lines[1].x, layout.lines[1].width), (0.0, 40.0));
+    }
+
+    #[test]
+    fn vertical_alignment() {
+        let options = |vertical_align| LayoutOptions {
+            max_width: Some(50.0),
+            box_height: Some(100.0),
+            vertical_align,
+            ..LayoutOptions::default()
+        };
+
+        let top = layout("aa bbbb", options(VerticalAlign::Top));
+        assert_eq!(top.lines[0].y, 0.0);
+
+        let middle = layout("aa bbbb", options(VerticalAlign::Middle));
+        assert_eq!(middle.lines[0].y, 38.0);
+        assert_eq!(middle.glyphs[2].position.y, 38.0 + LINE_HEIGHT);
+
+        let bottom = layout("aa bbbb", options(VerticalAlign::Bottom));
+        assert_eq!(bottom.lines[1].y + bottom.lines[1].height, 100.0);
+    }
+
+    #[test]
+    fn bounds() {
+        let layout = layout(
+            "aa bbbb",
+            wrapped(50.0, WrapMode::Word, HorizontalAlign::Center),
+        );
+        assert_eq!(
+            layout.bounds(),
+            (Vec2::new(5.0, 0.0), Vec2::new(40.0, 2.0 * LINE_HEIGHT))
+        );
+
+        assert_eq!(TextLayout::default().bounds(), (Vec2::zero(), Vec2::zero()));
+    }
+
+    #[test]
+    fn markup_styles_reach_glyphs() {
+        let text = StyledText::parse("a[wave]b[/wave]").unwrap();
+        let layout = TextLayout::new(&font(), &text, &LayoutOptions::default());
+
+        assert!(!layout.glyphs[0].style.wave);
+        assert!(layout.glyphs[1].style.wave);
+    }
+}
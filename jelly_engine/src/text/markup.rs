@@ -0,0 +1,226 @@
+use crate::error::{JellyError, Result};
+use crate::graphics::prelude::Color;
+
+/// Styling of a run of characters, set by markup tags
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct GlyphStyle {
+    /// Replaces the tint of the text, `None` keeps it
+    pub color: Option<Color>,
+    /// Characters bob up and down one after the other
+    pub wave: bool,
+    /// Characters jitter around their place
+    pub shake: bool,
+}
+
+/// Text split in runs of the same style.
+///
+/// Markup tags are `[color=red]` (palette name or `#RRGGBB`), `[wave]` and
+/// `[shake]`, each closed by `[/color]`, `[/wave]` and `[/shake]`. Tags
+/// nest, and `[[` is a literal `[`. Unclosed tags run to the end of the text.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StyledText {
+    pub spans: Vec<(String, GlyphStyle)>,
+}
+
+impl StyledText {
+    /// Text without any styling, brackets are not interpreted
+    pub fn plain(text: &str) -> StyledText {
+        StyledText {
+            spans: vec![(String::from(text), GlyphStyle::default())],
+        }
+    }
+
+    pub fn parse(markup: &str) -> Result<StyledText> {
+        let mut styled = StyledText::default();
+        let mut current = String::new();
+        let mut colors: Vec<Color> = Vec::new();
+        let mut waves = 0;
+        let mut shakes = 0;
+
+        let mut rest = markup;
+        while let Some(open) = rest.find('[') {
+            current.push_str(&rest[..open]);
+            rest = &rest[open + 1..];
+
+            if let Some(after) = rest.strip_prefix('[') {
+                current.push('[');
+                rest = after;
+                continue;
+            }
+
+            let close = rest
+                .find(']')
+                .ok_or_else(|| invalid(format!("unclosed tag in {}", markup)))?;
+            let tag = rest[..close].trim();
+            rest = &rest[close + 1..];
+
+            let style = GlyphStyle {
+                color: colors.last().copied(),
+                wave: waves > 0,
+                shake: shakes > 0,
+            };
+            styled.push(std::mem::take(&mut current), style);
+
+            let (name, value) = match tag.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim())),
+                None => (tag, None),
+            };
+            match (name, value) {
+                ("color", Some(value)) => colors.push(parse_color(value)?),
+                ("wave", None) => waves += 1,
+                ("shake", None) => shakes += 1,
+                ("/color", None) => {
+                    colors
+                        .pop()
+                        .ok_or_else(|| invalid("[/color] without [color]"))?;
+                }
+                ("/wave", None) => waves = close_tag(waves, "wave")?,
+                ("/shake", None) => shakes = close_tag(shakes, "shake")?,
+                _ => return Err(invalid(format!("unknown tag [{}]", tag))),
+            }
+        }
+        current.push_str(rest);
+
+        let style = GlyphStyle {
+            color: colors.last().copied(),
+            wave: waves > 0,
+            shake: shakes > 0,
+        };
+        styled.push(current, style);
+
+        Ok(styled)
+    }
+
+    /// The text without its markup
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|(text, _)| text.as_str()).collect()
+    }
+
+    /// Characters with their style
+    pub fn chars(&self) -> impl Iterator<Item = (char, GlyphStyle)> + '_ {
+        self.spans
+            .iter()
+            .flat_map(|(text, style)| text.chars().map(move |character| (character, *style)))
+    }
+
+    /// Add a run, merged with the last one when they share a style
+    fn push(&mut self, text: String, style: GlyphStyle) {
+        if text.is_empty() {
+            return;
+        }
+
+        match self.spans.last_mut() {
+            Some((last, last_style)) if *last_style == style => last.push_str(&text),
+            _ => self.spans.push((text, style)),
+        }
+    }
+}
+
+fn parse_color(value: &str) -> Result<Color> {
+    if value.starts_with('#') {
+        Ok(Color::from_hex(value)?)
+    } else {
+        Color::from_palette(value)
+    }
+}
+
+fn close_tag(depth: u32, name: &str) -> Result<u32> {
+    depth
+        .checked_sub(1)
+        .ok_or_else(|| invalid(format!("[/{}] without [{}]", name, name)))
+}
+
+fn invalid<S: Into<String>>(message: S) -> JellyError {
+    JellyError::InvalidMarkup(message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn style(color: Option<Color>, wave: bool, shake: bool) -> GlyphStyle {
+        GlyphStyle { color, wave, shake }
+    }
+
+    #[test]
+    fn nested_tags() {
+        let red = Color::from_palette("red").unwrap();
+        let green = Color::from_hex("#00ff00").unwrap();
+        let styled =
+            StyledText::parse("[color=red]a[wave]b[color=#00ff00]c[/color][/wave]d[/color]e")
+                .unwrap();
+
+        assert_eq!(
+            styled.spans,
+            vec![
+                (String::from("a"), style(Some(red), false, false)),
+                (String::from("b"), style(Some(red), true, false)),
+                (String::from("c"), style(Some(green), true, false)),
+                (String::from("d"), style(Some(red), false, false)),
+                (String::from("e"), GlyphStyle::default()),
+            ]
+        );
+        assert_eq!(styled.text(), "abcde");
+    }
+
+    #[test]
+    fn runs_of_the_same_style_merge() {
+        let styled = StyledText::parse("a[shake][/shake]b [ shake ]c").unwrap();
+
+        assert_eq!(
+            styled.spans,
+            vec![
+                (String::from("ab "), GlyphStyle::default()),
+                (String::from("c"), style(None, false, true)),
+            ]
+        );
+    }
+
+    #[test]
+    fn escaped_bracket() {
+        let styled = StyledText::parse("[[wave] [wave]x[/wave]").unwrap();
+
+        assert_eq!(styled.text(), "[wave] x");
+        assert_eq!(
+            styled.spans[0],
+            (String::from("[wave] "), GlyphStyle::default())
+        );
+        assert_eq!(styled.chars().last(), Some(('x', style(None, true, false))));
+    }
+
+    #[test]
+    fn unclosed_tags_run_to_the_end() {
+        let styled = StyledText::parse("a[wave]b[shake]c").unwrap();
+
+        assert_eq!(
+            styled.chars().map(|(_, style)| style).collect::<Vec<_>>(),
+            vec![
+                GlyphStyle::default(),
+                style(None, true, false),
+                style(None, true, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn plain_text_keeps_brackets() {
+        let styled = StyledText::plain("[wave]");
+
+        assert_eq!(styled.text(), "[wave]");
+        assert_eq!(styled.spans.len(), 1);
+    }
+
+    #[test]
+    fn invalid_markup() {
+        let is_invalid =
+            |markup: &str| matches!(StyledText::parse(markup), Err(JellyError::InvalidMarkup(_)));
+
+        assert!(is_invalid("a[/wave]"));
+        assert!(is_invalid("[wave]a[/shake]"));
+        assert!(is_invalid("[/color]"));
+        assert!(is_invalid("[bold]a"));
+        assert!(is_invalid("[wave=2]a"));
+        assert!(is_invalid("a[wave"));
+        assert!(StyledText::parse("[color=nope]a").is_err());
+    }
+}
@@ -0,0 +1,13 @@
+mod bmfont;
+mod debug_font;
+mod font;
+mod layout;
+mod markup;
+mod truetype;
+
+pub mod prelude {
+    pub use crate::text::font::*;
+    pub use crate::text::layout::*;
+    pub use crate::text::markup::*;
+    pub use crate::text::truetype::*;
+}
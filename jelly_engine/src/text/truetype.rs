@@ -0,0 +1,351 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use fontdue::FontSettings;
+
+use super::prelude::{Font, FontPage, Glyph};
+use crate::error::{JellyError, Result};
+use crate::graphics::prelude::{Color, SpriteBatch, Texture};
+use crate::math::prelude::{Matrix4x4, Vec2};
+
+const ATLAS_START_SIZE: u32 = 256;
+const ATLAS_MAX_SIZE: u32 = 4096;
+/// Empty pixels around each glyph, so filtering never bleeds
+const GLYPH_PADDING: u32 = 1;
+
+/// RGBA8 glyph images packed on shelves, kept on the CPU and uploaded when
+/// they change. Growing keeps the glyphs in place.
+struct GlyphAtlas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    // Next free spot, on a shelf as high as its tallest glyph
+    cursor_x: u32,
+    cursor_y: u32,
+    shelf_height: u32,
+    texture: Option<Texture>,
+    dirty: bool,
+}
+
+impl GlyphAtlas {
+    fn new() -> GlyphAtlas {
+        GlyphAtlas {
+            width: ATLAS_START_SIZE,
+            height: ATLAS_START_SIZE,
+            pixels: vec![0; (ATLAS_START_SIZE * ATLAS_START_SIZE * 4) as usize],
+            cursor_x: GLYPH_PADDING,
+            cursor_y: GLYPH_PADDING,
+            shelf_height: 0,
+            texture: None,
+            dirty: true,
+        }
+    }
+
+    /// Copy a coverage bitmap in white, returns its top left corner or
+    /// `None` once the atlas can't grow anymore
+    fn insert(&mut self, width: u32, height: u32, coverage: &[u8]) -> Option<(u32, u32)> {
+        if width == 0 || height == 0 {
+            return Some((0, 0));
+        }
+
+        while GLYPH_PADDING + width + GLYPH_PADDING > self.width {
+            self.resize(self.width * 2, self.height)?;
+        }
+        if self.cursor_x + width + GLYPH_PADDING > self.width {
+            self.cursor_x = GLYPH_PADDING;
+            self.cursor_y += self.shelf_height + GLYPH_PADDING;
+            self.shelf_height = 0;
+        }
+        while self.cursor_y + height + GLYPH_PADDING > self.height {
+            self.resize(self.width, self.height * 2)?;
+        }
+
+        let (x, y) = (self.cursor_x, self.cursor_y);
+        for row in 0..height {
+            for column in 0..width {
+                let alpha = coverage[(row * width + column) as usize];
+                let pixel = (((y + row) * self.width + x + column) * 4) as usize;
+                self.pixels[pixel..pixel + 4].copy_from_slice(&[255, 255, 255, alpha]);
+            }
+        }
+
+        self.cursor_x += width + GLYPH_PADDING;
+        self.shelf_height = self.shelf_height.max(height);
+        self.dirty = true;
+
+        Some((x, y))
+    }
+
+    /// Drop every glyph, the texture is kept
+    fn clear(&mut self) {
+        self.pixels.iter_mut().for_each(|pixel| *pixel = 0);
+        self.cursor_x = GLYPH_PADDING;
+        self.cursor_y = GLYPH_PADDING;
+        self.shelf_height = 0;
+        self.dirty = true;
+    }
+
+    fn resize(&mut self, width: u32, height: u32) -> Option<()> {
+        if width > ATLAS_MAX_SIZE || height > ATLAS_MAX_SIZE {
+            return None;
+        }
+
+        let mut pixels = vec![0; (width * height * 4) as usize];
+        let row_size = (self.width * 4) as usize;
+        for (row, source) in self.pixels.chunks(row_size).enumerate() {
+            let start = row * (width * 4) as usize;
+            pixels[start..start + row_size].copy_from_slice(source);
+        }
+
+        self.width = width;
+        self.height = height;
+        self.pixels = pixels;
+        self.dirty = true;
+
+        Some(())
+    }
+
+    /// Texture holding the current glyphs, recreated when the atlas grew
+    fn upload(&mut self) -> Texture {
+        match self.texture {
+            Some(texture) if texture.width() == self.width && texture.height() == self.height => {
+                if self.dirty {
+                    texture
+                        .update(&self.pixels)
+                        .expect("atlas pixels cover the texture");
+                }
+            }
+            previous => {
+                if let Some(previous) = previous {
+                    previous.delete();
+                }
+                self.texture = Some(
+                    Texture::from_pixels(self.width, self.height, &self.pixels)
+                        .expect("atlas pixels cover the texture"),
+                );
+            }
+        }
+
+        self.dirty = false;
+        self.texture.unwrap()
+    }
+}
+
+impl Drop for GlyphAtlas {
+    fn drop(&mut self) {
+        if let Some(texture) = self.texture.take() {
+            texture.delete();
+        }
+    }
+}
+
+/// A `TrueTypeFont` rasterized at one size
+struct SizedFont {
+    font: Font,
+    // What was already asked to the face, glyphs it doesn't have included.
+    // Glyphs dropped by a full atlas are left out, so they're tried again
+    rasterized: HashSet<char>,
+    // Glyphs the atlas had no room for, warned about once
+    dropped: HashSet<char>,
+    kerned: HashSet<(char, char)>,
+}
+
+/// Scalable `.ttf`/`.otf` font. Glyphs are rasterized the first time they're
+/// laid out at a size, into an atlas shared by every size.
+///
+/// Each size is a regular `Font`, see `font`. Its texture is freed with the
+/// `TrueTypeFont`, clones of these fonts can't draw past that.
+pub struct TrueTypeFont {
+    face: fontdue::Font,
+    name: String,
+    pixel_snap: bool,
+    atlas: GlyphAtlas,
+    // By size bits
+    sizes: HashMap<u32, SizedFont>,
+}
+
+impl TrueTypeFont {
+    pub fn from_bytes(data: &[u8]) -> Result<TrueTypeFont> {
+        let face = fontdue::Font::from_bytes(data, FontSettings::default())
+            .map_err(|e| JellyError::InvalidFont(e.to_string()))?;
+
+        Ok(TrueTypeFont {
+            name: face.name().unwrap_or_default().to_string(),
+            face,
+            pixel_snap: true,
+            atlas: GlyphAtlas::new(),
+            sizes: HashMap::new(),
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<TrueTypeFont> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(JellyError::AssetNotFound(path.to_path_buf()));
+        }
+
+        TrueTypeFont::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn pixel_snap(&self) -> bool {
+        self.pixel_snap
+    }
+
+    /// Round sizes, metrics and glyph positions to whole pixels, on by
+    /// default. Sizes already rasterized are dropped
+    pub fn set_pixel_snap(&mut self, pixel_snap: bool) {
+        if self.pixel_snap != pixel_snap {
+            self.pixel_snap = pixel_snap;
+            self.sizes.clear();
+            self.atlas.clear();
+        }
+    }
+
+    /// The font at `size` pixels, with the glyphs and kerning pairs of
+    /// `text` ready. Needs a GL context to update the atlas texture
+    pub fn font(&mut self, size: f32, text: &str) -> &Font {
+        let size = if self.pixel_snap { size.round() } else { size };
+        let key = size.to_bits();
+
+        if !self.sizes.contains_key(&key) {
+            let sized = self.new_size(size);
+            self.sizes.insert(key, sized);
+        }
+
+        let pixel_snap = self.pixel_snap;
+        let snap = |value: f32| if pixel_snap { value.round() } else { value };
+        let face = &self.face;
+        let atlas = &mut self.atlas;
+        let sized = self.sizes.get_mut(&key).unwrap();
+
+        for character in text.chars().chain(Some('?')) {
+            if sized.rasterized.contains(&character) {
+                continue;
+            }
+            if !face.has_glyph(character) {
+                sized.rasterized.insert(character);
+                continue;
+            }
+
+            let (metrics, coverage) = face.rasterize(character, size);
+            let (width, height) = (metrics.width as u32, metrics.height as u32);
+
+            match atlas.insert(width, height, &coverage) {
+                Some((x, y)) => {
+                    sized.rasterized.insert(character);
+                    sized.font.add_glyph(
+                        character,
+                        Glyph {
+                            page: 0,
+                            x,
+                            y,
+                            width,
+                            height,
+                            offset: Vec2::new(
+                                metrics.xmin as f32,
+                                sized.font.base - (metrics.ymin + metrics.height as i32) as f32,
+                            ),
+                            advance: snap(metrics.advance_width),
+                        },
+                    )
+                }
+                None => {
+                    if sized.dropped.insert(character) {
+                        log::warn!(
+                            "Glyph atlas of {} is full, dropping {:?} at {}px",
+                            self.name,
+                            character,
+                            size
+                        );
+                    }
+                }
+            }
+        }
+
+        for line in text.split('\n') {
+            let characters: Vec<char> = line.chars().collect();
+            for pair in characters.windows(2) {
+                if !sized.kerned.insert((pair[0], pair[1])) {
+                    continue;
+                }
+
+                if let Some(amount) = face.horizontal_kern(pair[0], pair[1], size) {
+                    if amount != 0.0 {
+                        sized.font.set_kerning(pair[0], pair[1], snap(amount));
+                    }
+                }
+            }
+        }
+
+        if atlas.dirty || atlas.texture.is_none() {
+            let texture = atlas.upload();
+            for sized in self.sizes.values_mut() {
+                sized.font.pages[0].texture = Some(texture);
+            }
+        }
+
+        &self.sizes[&key].font
+    }
+
+    /// Size of `text` at `size` pixels
+    pub fn measure(&mut self, text: &str, size: f32) -> Vec2 {
+        self.font(size, text).measure(text)
+    }
+
+    /// Queue `text` at `size` pixels, with its top left corner placed by `model`
+    pub fn draw(
+        &mut self,
+        batch: &mut SpriteBatch,
+        text: &str,
+        size: f32,
+        model: &Matrix4x4,
+        tint: Color,
+    ) {
+        self.font(size, text).draw(batch, text, model, tint);
+    }
+
+    /// Queue `text` at `size` pixels, with its top left corner at `position`
+    pub fn draw_at(
+        &mut self,
+        batch: &mut SpriteBatch,
+        text: &str,
+        size: f32,
+        position: Vec2,
+        tint: Color,
+    ) {
+        self.font(size, text).draw_at(batch, text, position, tint);
+    }
+
+    fn new_size(&self, size: f32) -> SizedFont {
+        let snap = |value: f32| {
+            if self.pixel_snap {
+                value.round()
+            } else {
+                value
+            }
+        };
+        let (ascent, line_height) = match self.face.horizontal_line_metrics(size) {
+            Some(metrics) => (metrics.ascent, metrics.new_line_size),
+            None => (size, size),
+        };
+
+        let mut font = Font::new(&self.name, size, snap(line_height), snap(ascent));
+        font.pixel_snap = self.pixel_snap;
+        font.pages.push(FontPage {
+            image: None,
+            texture: self.atlas.texture,
+        });
+
+        SizedFont {
+            font,
+            rasterized: HashSet::new(),
+            dropped: HashSet::new(),
+            kerned: HashSet::new(),
+        }
+    }
+}
@@ -0,0 +1,487 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::prelude::{Tile, TileLayer};
+use crate::error::{JellyError, Result};
+
+/// Kind of ground painted in a cell
+pub type Terrain = u16;
+
+/// Terrain of every cell, row by row from the top left one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerrainGrid {
+    pub width: u32,
+    pub height: u32,
+    cells: Vec<Option<Terrain>>,
+}
+
+impl TerrainGrid {
+    pub fn new(width: u32, height: u32) -> TerrainGrid {
+        TerrainGrid::from_cells(width, height, Vec::new())
+    }
+
+    /// Grid filled with `cells`, missing cells are left empty
+    pub fn from_cells(width: u32, height: u32, mut cells: Vec<Option<Terrain>>) -> TerrainGrid {
+        cells.resize((width * height) as usize, None);
+
+        TerrainGrid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// `None` for empty cells and outside of the grid
+    pub fn get(&self, x: u32, y: u32) -> Option<Terrain> {
+        self.index(x, y).and_then(|index| self.cells[index])
+    }
+
+    /// Returns false outside of the grid
+    pub fn set(&mut self, x: u32, y: u32, terrain: Option<Terrain>) -> bool {
+        match self.index(x, y) {
+            Some(index) => {
+                self.cells[index] = terrain;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn contains(&self, x: i64, y: i64) -> bool {
+        x >= 0 && y >= 0 && x < self.width as i64 && y < self.height as i64
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some((y * self.width + x) as usize)
+        } else {
+            None
+        }
+    }
+}
+
+/// Neighbours looked at to pick a tile, each one connected to the cell sets
+/// a bit of its mask.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BitmaskMode {
+    /// North 1, west 2, east 4, south 8, for 16 tiles
+    FourBit,
+    /// North west 1, north 2, north east 4, west 8, east 16, south west 32,
+    /// south 64, south east 128. Corners only count when both sides next to
+    /// them do, leaving the 47 tiles of a blob tileset
+    EightBit,
+}
+
+impl BitmaskMode {
+    /// Every mask a cell can get, in increasing order
+    pub fn masks(self) -> Vec<u8> {
+        match self {
+            BitmaskMode::FourBit => (0..16).collect(),
+            BitmaskMode::EightBit => (0..=255u8)
+                .filter(|&mask| reduce_corners(mask) == mask)
+                .collect(),
+        }
+    }
+
+    /// Neighbour offsets, by bit
+    fn neighbours(self) -> &'static [(i64, i64)] {
+        match self {
+            BitmaskMode::FourBit => &[(0, -1), (-1, 0), (1, 0), (0, 1)],
+            BitmaskMode::EightBit => &[
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ],
+        }
+    }
+}
+
+/// Drop the corners of an eight bit mask without both of their sides
+fn reduce_corners(mut mask: u8) -> u8 {
+    // (corner, sides)
+    for &(corner, sides) in &[(1, 2 | 8), (4, 2 | 16), (32, 64 | 8), (128, 64 | 16)] {
+        if mask & sides != sides {
+            mask &= !corner;
+        }
+    }
+
+    mask
+}
+
+/// Tiles of one terrain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TerrainRule {
+    /// Index of the tileset in the map
+    pub tileset: usize,
+    /// Tile id by mask
+    pub tiles: HashMap<u8, u32>,
+    /// Used for masks without tile, the cell is left empty otherwise
+    #[serde(default)]
+    pub fallback: Option<u32>,
+    /// Other terrains blending with this one, as if they were the same
+    #[serde(default)]
+    pub connects_to: Vec<Terrain>,
+}
+
+impl TerrainRule {
+    /// Rule for a tileset laid out like `mode.masks()`: the tiles from
+    /// `first_tile` on follow the masks in increasing order
+    pub fn sequential(mode: BitmaskMode, tileset: usize, first_tile: u32) -> TerrainRule {
+        TerrainRule {
+            tileset,
+            tiles: mode.masks().into_iter().zip(first_tile..).collect(),
+            fallback: None,
+            connects_to: Vec::new(),
+        }
+    }
+}
+
+/// How tiles are picked from the terrain around each cell.
+///
+/// ```ron
+/// (
+///     mode: EightBit,
+///     terrains: {
+///         1: (tileset: 0, tiles: { 0: 46, 255: 0 }, fallback: Some(0)),
+///     },
+/// )
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AutotileRules {
+    pub mode: BitmaskMode,
+    /// Whether the cells outside of the grid connect with every terrain
+    #[serde(default)]
+    pub border_connects: bool,
+    pub terrains: HashMap<Terrain, TerrainRule>,
+}
+
+impl AutotileRules {
+    pub fn new(mode: BitmaskMode) -> AutotileRules {
+        AutotileRules {
+            mode,
+            border_connects: false,
+            terrains: HashMap::new(),
+        }
+    }
+
+    pub fn from_ron(source: &str) -> Result<AutotileRules> {
+        ron::from_str(source).map_err(|e| JellyError::InvalidAutotileRules(e.to_string()))
+    }
+
+    pub fn to_ron(&self) -> Result<String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| JellyError::InvalidAutotileRules(e.to_string()))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<AutotileRules> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(JellyError::AssetNotFound(path.to_path_buf()));
+        }
+
+        AutotileRules::from_ron(&fs::read_to_string(path)?)
+    }
+
+    pub fn add_terrain(&mut self, terrain: Terrain, rule: TerrainRule) {
+        self.terrains.insert(terrain, rule);
+    }
+
+    /// Neighbours of cell (`x`, `y`) connected to it, `None` for empty cells
+    pub fn mask(&self, grid: &TerrainGrid, x: u32, y: u32) -> Option<u8> {
+        let terrain = grid.get(x, y)?;
+        let connects_to = self
+            .terrains
+            .get(&terrain)
+            .map_or(&[][..], |rule| &rule.connects_to[..]);
+
+        let mask = self
+            .mode
+            .neighbours()
+            .iter()
+            .enumerate()
+            .filter(|(_, &(dx, dy))| {
+                let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                if !grid.contains(nx, ny) {
+                    return self.border_connects;
+                }
+
+                grid.get(nx as u32, ny as u32)
+                    .is_some_and(|other| other == terrain || connects_to.contains(&other))
+            })
+            .fold(0, |mask, (bit, _)| mask | 1 << bit);
+
+        match self.mode {
+            BitmaskMode::FourBit => Some(mask),
+            BitmaskMode::EightBit => Some(reduce_corners(mask)),
+        }
+    }
+
+    /// Tile picked for cell (`x`, `y`), `None` when it's empty or its
+    /// terrain has no tile for its mask
+    pub fn tile(&self, grid: &TerrainGrid, x: u32, y: u32) -> Option<Tile> {
+        let rule = self.terrains.get(&grid.get(x, y)?)?;
+        let mask = self.mask(grid, x, y)?;
+        let id = rule.tiles.get(&mask).copied().or(rule.fallback)?;
+
+        Some(Tile::new(rule.tileset, id))
+    }
+}
+
+/// Terrain grid kept in sync with a tile layer.
+///
+/// Changing a cell only re-tiles it and its neighbours, so only the chunks
+/// around it get rebuilt.
+#[derive(Debug, Clone)]
+pub struct Autotiler {
+    pub rules: AutotileRules,
+    terrain: TerrainGrid,
+}
+
+impl Autotiler {
+    pub fn new(rules: AutotileRules, terrain: TerrainGrid) -> Autotiler {
+        Autotiler { rules, terrain }
+    }
+
+    pub fn terrain(&self) -> &TerrainGrid {
+        &self.terrain
+    }
+
+    /// Tile every cell of `layer` covered by the grid
+    pub fn apply(&self, layer: &mut TileLayer) {
+        for y in 0..self.terrain.height {
+            for x in 0..self.terrain.width {
+                layer.set(x, y, self.rules.tile(&self.terrain, x, y));
+            }
+        }
+    }
+
+    /// Paint cell (`x`, `y`) and re-tile it and its neighbours in `layer`.
+    /// Returns false outside of the grid
+    pub fn set_terrain(
+        &mut self,
+        layer: &mut TileLayer,
+        x: u32,
+        y: u32,
+        terrain: Option<Terrain>,
+    ) -> bool {
+        if !self.terrain.set(x, y, terrain) {
+            return false;
+        }
+
+        for ny in y.saturating_sub(1)..=y + 1 {
+            for nx in x.saturating_sub(1)..=x + 1 {
+                if self.terrain.contains(nx as i64, ny as i64) {
+                    layer.set(nx, ny, self.rules.tile(&self.terrain, nx, ny));
+                }
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRASS: Terrain = 1;
+    const WATER: Terrain = 2;
+
+    // '#' for grass, '~' for water, anything else is empty
+    fn grid(rows: &[&str]) -> TerrainGrid {
+        let cells = rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .map(|cell| match cell {
+                '#' => Some(GRASS),
+                '~' => Some(WATER),
+                _ => None,
+            })
+            .collect();
+
+        TerrainGrid::from_cells(rows[0].len() as u32, rows.len() as u32, cells)
+    }
+
+    fn rules(mode: BitmaskMode) -> AutotileRules {
+        let mut rules = AutotileRules::new(mode);
+        rules.add_terrain(GRASS, TerrainRule::sequential(mode, 0, 0));
+        rules.add_terrain(WATER, TerrainRule::sequential(mode, 1, 0));
+
+        rules
+    }
+
+    #[test]
+    fn corners_need_both_sides() {
+        assert_eq!(reduce_corners(0b1111_1111), 0b1111_1111);
+        // North west corner alone, then with north, then with north and west
+        assert_eq!(reduce_corners(1), 0);
+        assert_eq!(reduce_corners(1 | 2), 2);
+        assert_eq!(reduce_corners(1 | 2 | 8), 1 | 2 | 8);
+        // Every corner, no side
+        assert_eq!(reduce_corners(1 | 4 | 32 | 128), 0);
+        assert_eq!(reduce_corners(64 | 16 | 128 | 1), 64 | 16 | 128);
+    }
+
+    #[test]
+    fn masks() {
+        let four = BitmaskMode::FourBit.masks();
+        assert_eq!(four.len(), 16);
+        assert_eq!(four, (0..16).collect::<Vec<u8>>());
+
+        let eight = BitmaskMode::EightBit.masks();
+        assert_eq!(eight.len(), 47);
+        assert!(eight.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!((eight[0], eight[46]), (0, 255));
+        assert!(!eight.contains(&1));
+    }
+
+    #[test]
+    fn four_bit_masks() {
+        let rules = rules(BitmaskMode::FourBit);
+        let grid = grid(&[".#.", "###", ".#~"]);
+
+        assert_eq!(rules.mask(&grid, 1, 1), Some(1 | 2 | 4 | 8));
+        assert_eq!(rules.mask(&grid, 1, 0), Some(8));
+        assert_eq!(rules.mask(&grid, 0, 1), Some(4));
+        // Water doesn't connect to grass
+        assert_eq!(rules.mask(&grid, 1, 2), Some(1));
+        assert_eq!(rules.mask(&grid, 2, 2), Some(0));
+        assert_eq!(rules.mask(&grid, 0, 0), None);
+    }
+
+    #[test]
+    fn eight_bit_masks() {
+        let rules = rules(BitmaskMode::EightBit);
+        let grid = grid(&["##.", "###", "#.#"]);
+
+        // Every neighbour but north east and south, which also drops both south corners
+        assert_eq!(rules.mask(&grid, 1, 1), Some(1 | 2 | 8 | 16));
+        assert_eq!(rules.mask(&grid, 0, 0), Some(16 | 64 | 128));
+        // North east corner with both of its sides
+        assert_eq!(rules.mask(&grid, 0, 1), Some(2 | 4 | 16 | 64));
+        // North west corner without its west side
+        assert_eq!(rules.mask(&grid, 2, 2), Some(2));
+    }
+
+    #[test]
+    fn border_connects() {
+        let mut rules = rules(BitmaskMode::FourBit);
+        let grid = grid(&["#"]);
+
+        assert_eq!(rules.mask(&grid, 0, 0), Some(0));
+
+        rules.border_connects = true;
+        assert_eq!(rules.mask(&grid, 0, 0), Some(15));
+
+        rules.mode = BitmaskMode::EightBit;
+        assert_eq!(rules.mask(&grid, 0, 0), Some(255));
+    }
+
+    #[test]
+    fn connects_to() {
+        let mut rules = rules(BitmaskMode::FourBit);
+        rules
+            .terrains
+            .get_mut(&GRASS)
+            .unwrap()
+            .connects_to
+            .push(WATER);
+        let grid = grid(&["#~"]);
+
+        // Only one way
+        assert_eq!(rules.mask(&grid, 0, 0), Some(4));
+        assert_eq!(rules.mask(&grid, 1, 0), Some(0));
+    }
+
+    #[test]
+    fn tiles() {
+        let mut rules = rules(BitmaskMode::FourBit);
+        let grid = grid(&["##~"]);
+
+        assert_eq!(rules.tile(&grid, 0, 0), Some(Tile::new(0, 4)));
+        assert_eq!(rules.tile(&grid, 1, 0), Some(Tile::new(0, 2)));
+        assert_eq!(rules.tile(&grid, 2, 0), Some(Tile::new(1, 0)));
+
+        rules.terrains.get_mut(&WATER).unwrap().tiles.remove(&0);
+        assert_eq!(rules.tile(&grid, 2, 0), None);
+        rules.terrains.get_mut(&WATER).unwrap().fallback = Some(9);
+        assert_eq!(rules.tile(&grid, 2, 0), Some(Tile::new(1, 9)));
+
+        // Terrain without rule
+        rules.terrains.remove(&GRASS);
+        assert_eq!(rules.tile(&grid, 0, 0), None);
+    }
+
+    #[test]
+    fn sequential_rules_follow_the_masks() {
+        let rule = TerrainRule::sequential(BitmaskMode::EightBit, 2, 10);
+
+        assert_eq!(rule.tileset, 2);
+        assert_eq!(rule.tiles.len(), 47);
+        assert_eq!(rule.tiles[&0], 10);
+        assert_eq!(rule.tiles[&2], 11);
+        assert_eq!(rule.tiles[&255], 56);
+    }
+
+    #[test]
+    fn rules_from_ron() {
+        let rules = AutotileRules::from_ron(
+            "(mode: EightBit, terrains: { 1: (tileset: 0, tiles: { 0: 46, 255: 0 }, fallback: Some(0)) })",
+        )
+        .unwrap();
+
+        assert!(!rules.border_connects);
+        assert_eq!(rules.terrains[&1].tiles[&0], 46);
+        assert_eq!(
+            AutotileRules::from_ron(&rules.to_ron().unwrap()).unwrap(),
+            rules
+        );
+
+        assert!(matches!(
+            AutotileRules::from_ron("(mode: NineBit, terrains: {})"),
+            Err(JellyError::InvalidAutotileRules(_))
+        ));
+    }
+
+    #[test]
+    fn set_terrain_retiles_the_neighbours() {
+        // Across the first two chunks
+        let rows = vec!["#".repeat(20); 3];
+        let rows: Vec<&str> = rows.iter().map(String::as_str).collect();
+        let mut autotiler = Autotiler::new(rules(BitmaskMode::EightBit), grid(&rows));
+
+        let mut layer = TileLayer::new("ground", 20, 3);
+        autotiler.apply(&mut layer);
+        assert_eq!(layer.get(1, 1), Some(Tile::new(0, 46)));
+        layer.take_dirty_chunks();
+
+        assert!(autotiler.set_terrain(&mut layer, 1, 1, Some(WATER)));
+        assert!(!autotiler.set_terrain(&mut layer, 20, 0, Some(WATER)));
+        assert_eq!(autotiler.terrain().get(1, 1), Some(WATER));
+        assert_eq!(
+            layer.take_dirty_chunks().into_iter().collect::<Vec<_>>(),
+            [(0, 0)]
+        );
+
+        // Same tiles as tiling the whole layer again
+        let mut expected = TileLayer::new("ground", 20, 3);
+        autotiler.apply(&mut expected);
+        assert_eq!(
+            layer.tiles().collect::<Vec<_>>(),
+            expected.tiles().collect::<Vec<_>>()
+        );
+        assert_eq!(layer.get(1, 1), Some(Tile::new(1, 0)));
+
+        autotiler.set_terrain(&mut layer, 16, 1, None);
+        assert_eq!(layer.get(16, 1), None);
+        assert_eq!(layer.take_dirty_chunks().len(), 2);
+    }
+}
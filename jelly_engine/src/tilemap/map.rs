@@ -0,0 +1,272 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::prelude::{ObjectLayer, Properties, Tileset};
+use super::render::TilemapMeshes;
+use crate::error::Result;
+use crate::graphics::prelude::Texture;
+use crate::math::prelude::Vec2;
+
+/// Side of the square blocks of tiles drawn with one buffer per tileset
+pub(super) const CHUNK_SIZE: u32 = 16;
+
+/// Cell of a tile layer
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Tile {
+    /// Index of the tileset in the map
+    pub tileset: usize,
+    /// Tile in its tileset
+    pub id: u32,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// Swap the x and y axes before the other flips, like Tiled's diagonal flip
+    pub flip_diagonal: bool,
+}
+
+impl Tile {
+    pub fn new(tileset: usize, id: u32) -> Tile {
+        Tile {
+            tileset,
+            id,
+            flip_x: false,
+            flip_y: false,
+            flip_diagonal: false,
+        }
+    }
+
+    pub fn flipped(mut self, flip_x: bool, flip_y: bool) -> Tile {
+        self.flip_x ^= flip_x;
+        self.flip_y ^= flip_y;
+        self
+    }
+}
+
+/// Grid of tiles, row by row from the top left one
+#[derive(Debug, Clone)]
+pub struct TileLayer {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub visible: bool,
+    pub opacity: f32,
+    /// In pixels
+    pub offset: Vec2,
+    pub properties: Properties,
+    tiles: Vec<Option<Tile>>,
+    // Chunks edited since they were last built
+    dirty_chunks: HashSet<(u32, u32)>,
+}
+
+impl TileLayer {
+    pub fn new(name: &str, width: u32, height: u32) -> TileLayer {
+        TileLayer::from_tiles(name, width, height, Vec::new())
+    }
+
+    /// Layer filled with `tiles`, missing cells are left empty
+    pub fn from_tiles(
+        name: &str,
+        width: u32,
+        height: u32,
+        mut tiles: Vec<Option<Tile>>,
+    ) -> TileLayer {
+        tiles.resize((width * height) as usize, None);
+
+        TileLayer {
+            name: String::from(name),
+            width,
+            height,
+            visible: true,
+            opacity: 1.0,
+            offset: Vec2::zero(),
+            properties: Properties::new(),
+            tiles,
+            dirty_chunks: HashSet::new(),
+        }
+    }
+
+    /// `None` for empty cells and outside of the layer
+    pub fn get(&self, x: u32, y: u32) -> Option<Tile> {
+        self.index(x, y).and_then(|index| self.tiles[index])
+    }
+
+    /// Returns false outside of the layer
+    pub fn set(&mut self, x: u32, y: u32, tile: Option<Tile>) -> bool {
+        let index = match self.index(x, y) {
+            Some(index) => index,
+            None => return false,
+        };
+
+        if self.tiles[index] != tile {
+            self.tiles[index] = tile;
+            self.dirty_chunks.insert((x / CHUNK_SIZE, y / CHUNK_SIZE));
+        }
+
+        true
+    }
+
+    /// Non empty cells, as (x, y, tile)
+    pub fn tiles(&self) -> impl Iterator<Item = (u32, u32, Tile)> + '_ {
+        let width = self.width.max(1);
+
+        self.tiles
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, tile)| {
+                tile.map(|tile| (index as u32 % width, index as u32 / width, tile))
+            })
+    }
+
+    pub(super) fn take_dirty_chunks(&mut self) -> HashSet<(u32, u32)> {
+        std::mem::take(&mut self.dirty_chunks)
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some((y * self.width + x) as usize)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Layer {
+    Tiles(TileLayer),
+    Objects(ObjectLayer),
+}
+
+impl Layer {
+    pub fn name(&self) -> &str {
+        match self {
+            Layer::Tiles(layer) => &layer.name,
+            Layer::Objects(layer) => &layer.name,
+        }
+    }
+}
+
+/// Layers of tiles and objects on a grid of `tile_width`x`tile_height` pixels.
+///
+/// Tile layers are drawn by chunks of static geometry, built the first time
+/// they're visible and again after their tiles change. Animated tiles are
+/// drawn through the `SpriteBatch` instead.
+pub struct Tilemap {
+    /// In tiles
+    pub width: u32,
+    pub height: u32,
+    /// In pixels
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tilesets: Vec<Tileset>,
+    /// In drawing order, call `invalidate` after adding or removing some directly
+    pub layers: Vec<Layer>,
+    pub properties: Properties,
+    /// Only cameras seeing this layer draw the map, see `LayerMask`
+    pub render_layer: u8,
+
+    // Seconds, drives the animated tiles
+    pub(super) time: f32,
+    pub(super) meshes: TilemapMeshes,
+}
+
+impl Tilemap {
+    pub fn new(width: u32, height: u32, tile_width: u32, tile_height: u32) -> Tilemap {
+        Tilemap {
+            width,
+            height,
+            tile_width,
+            tile_height,
+            tilesets: Vec::new(),
+            layers: Vec::new(),
+            properties: Properties::new(),
+            render_layer: 0,
+            time: 0.0,
+            meshes: TilemapMeshes::default(),
+        }
+    }
+
+    /// Returns the index of the tileset, used by `Tile::tileset`
+    pub fn add_tileset(&mut self, tileset: Tileset) -> usize {
+        self.tilesets.push(tileset);
+        self.tilesets.len() - 1
+    }
+
+    /// Add a layer on top of the others
+    pub fn add_layer(&mut self, layer: Layer) {
+        self.layers.push(layer);
+    }
+
+    /// Size of the map, in pixels
+    pub fn pixel_size(&self) -> Vec2 {
+        Vec2::new(
+            (self.width * self.tile_width) as f32,
+            (self.height * self.tile_height) as f32,
+        )
+    }
+
+    pub fn tile_layer(&self, name: &str) -> Option<&TileLayer> {
+        self.layers.iter().find_map(|layer| match layer {
+            Layer::Tiles(layer) if layer.name == name => Some(layer),
+            _ => None,
+        })
+    }
+
+    pub fn tile_layer_mut(&mut self, name: &str) -> Option<&mut TileLayer> {
+        self.layers.iter_mut().find_map(|layer| match layer {
+            Layer::Tiles(layer) if layer.name == name => Some(layer),
+            _ => None,
+        })
+    }
+
+    pub fn object_layer(&self, name: &str) -> Option<&ObjectLayer> {
+        self.layers.iter().find_map(|layer| match layer {
+            Layer::Objects(layer) if layer.name == name => Some(layer),
+            _ => None,
+        })
+    }
+
+    /// Top left corner of the cell (`x`, `y`), in pixels
+    pub fn tile_to_world(&self, x: u32, y: u32) -> Vec2 {
+        Vec2::new((x * self.tile_width) as f32, (y * self.tile_height) as f32)
+    }
+
+    /// Cell under `position`, `None` outside of the map
+    pub fn world_to_tile(&self, position: Vec2) -> Option<(u32, u32)> {
+        if position.x < 0.0 || position.y < 0.0 {
+            return None;
+        }
+
+        let x = (position.x / self.tile_width.max(1) as f32) as u32;
+        let y = (position.y / self.tile_height.max(1) as f32) as u32;
+        if x < self.width && y < self.height {
+            Some((x, y))
+        } else {
+            None
+        }
+    }
+
+    /// Rebuild the geometry of every layer on the next draw
+    pub fn invalidate(&mut self) {
+        self.meshes.clear();
+    }
+
+    /// Advance the animated tiles
+    pub fn update(&mut self, dt: f32) {
+        self.time += dt;
+    }
+
+    /// Load the image of every tileset still without texture
+    pub fn load_textures(&mut self) -> Result<()> {
+        for tileset in &mut self.tilesets {
+            if let (None, Some(image)) = (&tileset.texture, &tileset.image) {
+                tileset.texture = Some(Texture::from_file(image)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read a Tiled map, `.tmx` or `.json`/`.tmj`, without loading its textures
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Tilemap> {
+        super::tiled::load(path.as_ref())
+    }
+}
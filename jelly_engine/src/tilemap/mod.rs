@@ -0,0 +1,13 @@
+mod autotile;
+mod map;
+mod object;
+mod render;
+mod tiled;
+mod tileset;
+
+pub mod prelude {
+    pub use crate::tilemap::autotile::*;
+    pub use crate::tilemap::map::*;
+    pub use crate::tilemap::object::*;
+    pub use crate::tilemap::tileset::*;
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use super::prelude::Tile;
+use crate::graphics::prelude::Color;
+use crate::math::prelude::Vec2;
+
+/// Custom property set in the map editor
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Color(Color),
+    /// Path relative to the map file
+    File(String),
+    /// Id of another object of the map, 0 when unset
+    Object(u32),
+}
+
+impl PropertyValue {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            PropertyValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            PropertyValue::Int(value) => Some(*value),
+            PropertyValue::Object(id) => Some(*id as i64),
+            _ => None,
+        }
+    }
+
+    /// Int properties are converted
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            PropertyValue::Float(value) => Some(*value),
+            PropertyValue::Int(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    /// String and file properties
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            PropertyValue::String(value) | PropertyValue::File(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_color(&self) -> Option<Color> {
+        match self {
+            PropertyValue::Color(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+pub type Properties = HashMap<String, PropertyValue>;
+
+/// Outline of an object, points are relative to the object position
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectShape {
+    Rectangle,
+    Ellipse,
+    Point,
+    Polygon(Vec<Vec2>),
+    Polyline(Vec<Vec2>),
+    /// Tile stretched over the object
+    Tile(Tile),
+}
+
+/// Object placed on an object layer
+#[derive(Debug, Clone)]
+pub struct MapObject {
+    pub id: u32,
+    pub name: String,
+    /// Class of the object, called type before Tiled 1.9
+    pub class: String,
+    /// Top left corner, in pixels. Tiled places tile objects by their bottom
+    /// left corner, they're moved up by their height on import
+    pub position: Vec2,
+    pub width: f32,
+    pub height: f32,
+    /// Clockwise, in radians
+    pub rotation: f32,
+    pub visible: bool,
+    pub shape: ObjectShape,
+    pub properties: Properties,
+}
+
+impl MapObject {
+    pub fn property(&self, name: &str) -> Option<&PropertyValue> {
+        self.properties.get(name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjectLayer {
+    pub name: String,
+    pub objects: Vec<MapObject>,
+    pub visible: bool,
+    /// In pixels
+    pub offset: Vec2,
+    pub properties: Properties,
+}
+
+impl ObjectLayer {
+    pub fn new(name: &str) -> ObjectLayer {
+        ObjectLayer {
+            name: String::from(name),
+            objects: Vec::new(),
+            visible: true,
+            offset: Vec2::zero(),
+            properties: Properties::new(),
+        }
+    }
+
+    /// First object called `name`
+    pub fn object(&self, name: &str) -> Option<&MapObject> {
+        self.objects.iter().find(|object| object.name == name)
+    }
+
+    pub fn objects_of_class<'a>(&'a self, class: &'a str) -> impl Iterator<Item = &'a MapObject> {
+        self.objects
+            .iter()
+            .filter(move |object| object.class == class)
+    }
+}
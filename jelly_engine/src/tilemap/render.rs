@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+
+use super::map::CHUNK_SIZE;
+use super::prelude::{Layer, Tile, TileLayer, Tilemap, Tileset};
+use crate::engine::prelude::Context;
+use crate::error::Result;
+use crate::gl_utilities::prelude::{GLbuffer, Shader, VertexFormat};
+use crate::graphics::prelude::{quad_indices, Color, ColoredVertex, Quad, TextureRegion};
+use crate::math::prelude::{Matrix4x4, Vec2, Vec3};
+
+/// Static geometry of one chunk of a tile layer
+struct ChunkMesh {
+    // What the mesh was built with, it's rebuilt when any of them changes
+    program: gl::types::GLuint,
+    opacity: f32,
+    textures: Vec<Option<u32>>,
+
+    /// One buffer per tileset
+    parts: Vec<(usize, GLbuffer)>,
+    /// Drawn every frame through the sprite batch, as (x, y, tile)
+    animated: Vec<(u32, u32, Tile)>,
+}
+
+/// GL side of a `Tilemap`, chunk meshes by (layer, chunk x, chunk y)
+#[derive(Default)]
+pub(super) struct TilemapMeshes {
+    chunks: HashMap<(usize, u32, u32), ChunkMesh>,
+}
+
+impl TilemapMeshes {
+    pub(super) fn clear(&mut self) {
+        self.chunks.clear();
+    }
+}
+
+/// Shader inputs the chunks are drawn with
+struct ChunkShader {
+    program: gl::types::GLuint,
+    attribute_locations: Vec<gl::types::GLuint>,
+    u_projection_location: gl::types::GLint,
+    u_diffuse_location: gl::types::GLint,
+}
+
+impl ChunkShader {
+    fn new(shader: &Shader) -> Result<ChunkShader> {
+        Ok(ChunkShader {
+            program: shader.program,
+            attribute_locations: ColoredVertex::layout().locations(shader)?,
+            u_projection_location: shader.get_uniform_location("u_projection")?,
+            u_diffuse_location: shader.get_uniform_location("u_diffuse")?,
+        })
+    }
+}
+
+impl Tilemap {
+    /// Draw the visible layers through the camera being rendered, with the
+    /// `batch` shader. Does nothing when headless.
+    pub fn draw(&mut self, ctx: &mut Context) -> Result<()> {
+        if ctx.is_headless() || !ctx.batch.layers().contains(self.render_layer) {
+            return Ok(());
+        }
+
+        let camera = ctx.rendering_camera();
+        let view_projection = camera.view_projection();
+        let (view_min, view_max) = camera.visible_area();
+
+        let shader = ChunkShader::new(ctx.shader_manager.get("batch")?)?;
+        let textures: Vec<Option<u32>> = self
+            .tilesets
+            .iter()
+            .map(|tileset| tileset.texture.map(|texture| texture.id()))
+            .collect();
+
+        // Whatever was queued before the map goes under it
+        ctx.batch.flush();
+
+        let chunk_width = (CHUNK_SIZE * self.tile_width) as f32;
+        let chunk_height = (CHUNK_SIZE * self.tile_height) as f32;
+
+        for (index, layer) in self.layers.iter_mut().enumerate() {
+            let layer = match layer {
+                Layer::Tiles(layer) if layer.visible => layer,
+                Layer::Tiles(layer) => {
+                    // Still rebuilt once visible again
+                    for chunk in layer.take_dirty_chunks() {
+                        self.meshes.chunks.remove(&(index, chunk.0, chunk.1));
+                    }
+                    continue;
+                }
+                Layer::Objects(_) => continue,
+            };
+
+            for chunk in layer.take_dirty_chunks() {
+                self.meshes.chunks.remove(&(index, chunk.0, chunk.1));
+            }
+
+            // Chunks overlapping the view, tiles taller than the grid reach into the chunk above
+            let min = view_min - layer.offset;
+            let max = view_max - layer.offset + Vec2::new(0.0, chunk_height);
+            let columns = layer.width.div_ceil(CHUNK_SIZE);
+            let rows = layer.height.div_ceil(CHUNK_SIZE);
+            let first_x = (min.x / chunk_width).floor().max(0.0) as u32;
+            let first_y = (min.y / chunk_height).floor().max(0.0) as u32;
+            let last_x = ((max.x / chunk_width).ceil().max(0.0) as u32).min(columns);
+            let last_y = ((max.y / chunk_height).ceil().max(0.0) as u32).min(rows);
+
+            let model = Matrix4x4::translation(Vec3::new(layer.offset.x, layer.offset.y, 0.0));
+            let projection = view_projection * model;
+            let tint = Color {
+                a: layer.opacity,
+                ..Color::white()
+            };
+
+            for chunk_y in first_y..last_y {
+                for chunk_x in first_x..last_x {
+                    let key = (index, chunk_x, chunk_y);
+                    let stale = self.meshes.chunks.get(&key).is_none_or(|mesh| {
+                        mesh.program != shader.program
+                            || mesh.opacity != layer.opacity
+                            || mesh.textures != textures
+                    });
+                    if stale {
+                        let mesh = build_chunk(
+                            layer,
+                            &self.tilesets,
+                            (self.tile_width, self.tile_height),
+                            (chunk_x, chunk_y),
+                            &shader,
+                            &textures,
+                        );
+                        self.meshes.chunks.insert(key, mesh);
+                    }
+
+                    let mesh = &self.meshes.chunks[&key];
+                    draw_chunk(mesh, &self.tilesets, &shader, &projection);
+
+                    for &(x, y, tile) in &mesh.animated {
+                        let tileset = &self.tilesets[tile.tileset];
+                        let frame = Tile {
+                            id: tileset.animated_tile(tile.id, self.time),
+                            ..tile
+                        };
+                        if let Some(region) = tileset.region(frame.id) {
+                            let (position, _) =
+                                tile_rect(x, y, (self.tile_width, self.tile_height), tileset);
+                            draw_animated(
+                                ctx,
+                                &region,
+                                frame,
+                                position + layer.offset,
+                                tint,
+                                self.render_layer,
+                            );
+                        }
+                    }
+                }
+            }
+
+            // Keep the animated tiles of this layer under the next one
+            ctx.batch.flush();
+        }
+
+        Ok(())
+    }
+}
+
+/// Top left corner and size of the tile drawn in cell (`x`, `y`). Tiles
+/// bigger than the grid stick out at the top, like in Tiled
+fn tile_rect(x: u32, y: u32, grid: (u32, u32), tileset: &Tileset) -> (Vec2, Vec2) {
+    let size = Vec2::new(tileset.tile_width() as f32, tileset.tile_height() as f32);
+    let position = Vec2::new((x * grid.0) as f32, ((y + 1) * grid.1) as f32 - size.y);
+
+    (position, size)
+}
+
+fn build_chunk(
+    layer: &TileLayer,
+    tilesets: &[Tileset],
+    grid: (u32, u32),
+    chunk: (u32, u32),
+    shader: &ChunkShader,
+    textures: &[Option<u32>],
+) -> ChunkMesh {
+    let tint = Color {
+        a: layer.opacity,
+        ..Color::white()
+    };
+
+    let mut vertices: HashMap<usize, Vec<ColoredVertex>> = HashMap::new();
+    let mut animated = Vec::new();
+
+    let first_x = chunk.0 * CHUNK_SIZE;
+    let first_y = chunk.1 * CHUNK_SIZE;
+    for y in first_y..(first_y + CHUNK_SIZE).min(layer.height) {
+        for x in first_x..(first_x + CHUNK_SIZE).min(layer.width) {
+            let tile = match layer.get(x, y) {
+                Some(tile) => tile,
+                None => continue,
+            };
+            let tileset = match tilesets.get(tile.tileset) {
+                Some(tileset) => tileset,
+                None => continue,
+            };
+
+            if tileset.is_animated(tile.id) {
+                animated.push((x, y, tile));
+                continue;
+            }
+
+            let region = match tileset.region(tile.id) {
+                Some(region) => region,
+                None => continue,
+            };
+
+            let (position, size) = tile_rect(x, y, grid, tileset);
+            let uv = region.uv();
+            let corner = |corner_x: f32, corner_y: f32| {
+                // Texture point shown at this corner, flips undone in reverse order
+                let mut source = (corner_x, corner_y);
+                if tile.flip_x {
+                    source.0 = 1.0 - source.0;
+                }
+                if tile.flip_y {
+                    source.1 = 1.0 - source.1;
+                }
+                if tile.flip_diagonal {
+                    source = (source.1, source.0);
+                }
+
+                ColoredVertex::new(
+                    Vec3::new(
+                        position.x + corner_x * size.x,
+                        position.y + corner_y * size.y,
+                        0.0,
+                    ),
+                    Vec2::new(
+                        uv.u0 + (uv.u1 - uv.u0) * source.0,
+                        uv.v0 + (uv.v1 - uv.v0) * source.1,
+                    ),
+                    tint,
+                )
+            };
+
+            vertices
+                .entry(tile.tileset)
+                .or_default()
+                .extend_from_slice(&[
+                    corner(0.0, 0.0),
+                    corner(0.0, 1.0),
+                    corner(1.0, 1.0),
+                    corner(1.0, 0.0),
+                ]);
+        }
+    }
+
+    let parts = vertices
+        .into_iter()
+        .map(|(tileset, vertices)| {
+            let mut buffer = GLbuffer::new();
+            buffer.configure_for::<ColoredVertex>(&shader.attribute_locations);
+            buffer.upload(&vertices);
+            buffer.upload_indices(&quad_indices(vertices.len() / 4));
+
+            (tileset, buffer)
+        })
+        .collect();
+
+    ChunkMesh {
+        program: shader.program,
+        opacity: layer.opacity,
+        textures: textures.to_vec(),
+        parts,
+        animated,
+    }
+}
+
+fn draw_chunk(
+    mesh: &ChunkMesh,
+    tilesets: &[Tileset],
+    shader: &ChunkShader,
+    projection: &Matrix4x4,
+) {
+    if mesh.parts.is_empty() {
+        return;
+    }
+
+    unsafe {
+        let mut previous_program = 0;
+        gl::GetIntegerv(gl::CURRENT_PROGRAM, &mut previous_program);
+
+        gl::UseProgram(shader.program);
+        gl::UniformMatrix4fv(
+            shader.u_projection_location,
+            1,
+            gl::FALSE,
+            projection.data.as_ptr(),
+        );
+        gl::Uniform1i(shader.u_diffuse_location, 0);
+
+        for (tileset, buffer) in &mesh.parts {
+            if let Some(texture) = tilesets[*tileset].texture {
+                texture.activate();
+                buffer.draw();
+            }
+        }
+
+        gl::UseProgram(previous_program as gl::types::GLuint);
+    }
+}
+
+/// Queue an animated tile in the sprite batch at `position`, its top left corner
+fn draw_animated(
+    ctx: &mut Context,
+    region: &TextureRegion,
+    tile: Tile,
+    position: Vec2,
+    tint: Color,
+    layer: u8,
+) {
+    let mut model = Matrix4x4::translation(Vec3::new(position.x, position.y, 0.0));
+    let (flip_x, flip_y) = if tile.flip_diagonal {
+        // Mirror the quad over its diagonal, the flips then apply to the other axis
+        let mut transpose = Matrix4x4::identity();
+        transpose.data[0] = 0.0;
+        transpose.data[1] = 1.0;
+        transpose.data[4] = 1.0;
+        transpose.data[5] = 0.0;
+        model *= transpose;
+
+        (tile.flip_y, tile.flip_x)
+    } else {
+        (tile.flip_x, tile.flip_y)
+    };
+
+    let quad = Quad {
+        tint,
+        layer,
+        ..Quad::from_region(&region.flipped(flip_x, flip_y))
+    };
+
+    ctx.batch.draw(&region.texture, &quad, &model);
+}
@@ -0,0 +1,1165 @@
+// Import of maps made with the Tiled editor (https://www.mapeditor.org).
+//
+// Orthogonal, finite maps are supported in both the TMX (XML) and JSON
+// formats, with CSV or base64 tile data, uncompressed or compressed with zlib
+// or gzip, and inline or external tilesets. Group layers are flattened, image layers are skipped.
+
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+use base64::Engine;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use roxmltree::Node;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::prelude::{
+    Layer, MapObject, ObjectLayer, ObjectShape, Properties, PropertyValue, Tile, TileFrame,
+    TileLayer, Tilemap, Tileset,
+};
+use crate::error::{JellyError, Result};
+use crate::graphics::prelude::{Color, Grid};
+use crate::math::prelude::Vec2;
+
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+// Only meaningful on hexagonal maps
+const ROTATED_HEXAGONAL_120: u32 = 0x1000_0000;
+const GID_MASK: u32 =
+    !(FLIPPED_HORIZONTALLY | FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY | ROTATED_HEXAGONAL_120);
+
+pub(super) fn load(path: &Path) -> Result<Tilemap> {
+    if !path.exists() {
+        return Err(JellyError::AssetNotFound(path.to_path_buf()));
+    }
+
+    let source = fs::read_to_string(path)?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    match extension(path).as_str() {
+        "tmx" => Tilemap::from_tmx(&source, dir),
+        "json" | "tmj" => Tilemap::from_tiled_json(&source, dir),
+        _ => Err(invalid(format!("unknown map format {}", path.display()))),
+    }
+}
+
+impl Tilemap {
+    /// Read a TMX map, external tilesets and images are relative to `dir`
+    pub fn from_tmx(source: &str, dir: &Path) -> Result<Tilemap> {
+        let document = roxmltree::Document::parse(source).map_err(|e| invalid(e.to_string()))?;
+        let root = document.root_element();
+        if root.tag_name().name() != "map" {
+            return Err(invalid("missing map element"));
+        }
+
+        let mut map = new_map(
+            &attribute::<String>(root, "orientation")?.unwrap_or_default(),
+            attribute::<u32>(root, "infinite")?.unwrap_or(0) != 0,
+            (required(root, "width")?, required(root, "height")?),
+            (required(root, "tilewidth")?, required(root, "tileheight")?),
+        )?;
+        map.properties = tmx_properties(root)?;
+
+        let mut gids = Gids::default();
+        for node in root.children().filter(|node| node.has_tag_name("tileset")) {
+            let first_gid = required(node, "firstgid")?;
+            let tileset = match node.attribute("source") {
+                Some(source) => load_tileset(&dir.join(source))?,
+                None => tmx_tileset(node, dir)?,
+            };
+
+            gids.add(first_gid, map.add_tileset(tileset));
+        }
+
+        tmx_layers(&mut map, &gids, root, &Group::default())?;
+
+        Ok(map)
+    }
+
+    /// Read a JSON map, external tilesets and images are relative to `dir`
+    pub fn from_tiled_json(source: &str, dir: &Path) -> Result<Tilemap> {
+        let json: JsonMap = serde_json::from_str(source).map_err(|e| invalid(e.to_string()))?;
+
+        let mut map = new_map(
+            &json.orientation,
+            json.infinite,
+            (json.width, json.height),
+            (json.tilewidth, json.tileheight),
+        )?;
+        map.properties = json_properties(&json.properties)?;
+
+        let mut gids = Gids::default();
+        for tileset in &json.tilesets {
+            let first_gid = tileset
+                .firstgid
+                .ok_or_else(|| invalid("tileset without firstgid"))?;
+            let tileset = match &tileset.source {
+                Some(source) => load_tileset(&dir.join(source))?,
+                None => json_tileset(tileset, dir)?,
+            };
+
+            gids.add(first_gid, map.add_tileset(tileset));
+        }
+
+        json_layers(&mut map, &gids, &json.layers, &Group::default())?;
+
+        Ok(map)
+    }
+}
+
+fn new_map(
+    orientation: &str,
+    infinite: bool,
+    size: (u32, u32),
+    tile_size: (u32, u32),
+) -> Result<Tilemap> {
+    if orientation != "orthogonal" {
+        return Err(invalid(format!(
+            "{} maps are not supported, only orthogonal ones",
+            orientation
+        )));
+    }
+    if infinite {
+        return Err(invalid("infinite maps are not supported"));
+    }
+
+    Ok(Tilemap::new(size.0, size.1, tile_size.0, tile_size.1))
+}
+
+fn invalid<S: Into<String>>(message: S) -> JellyError {
+    JellyError::InvalidTilemap(message.into())
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+/// Read an external tileset, `.tsx` or `.json`/`.tsj`
+fn load_tileset(path: &Path) -> Result<Tileset> {
+    if !path.exists() {
+        return Err(JellyError::AssetNotFound(path.to_path_buf()));
+    }
+
+    let source = fs::read_to_string(path)?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    match extension(path).as_str() {
+        "tsx" => {
+            let document =
+                roxmltree::Document::parse(&source).map_err(|e| invalid(e.to_string()))?;
+            tmx_tileset(document.root_element(), dir)
+        }
+        "json" | "tsj" => {
+            let json: JsonTileset =
+                serde_json::from_str(&source).map_err(|e| invalid(e.to_string()))?;
+            json_tileset(&json, dir)
+        }
+        _ => Err(invalid(format!(
+            "unknown tileset format {}",
+            path.display()
+        ))),
+    }
+}
+
+/// Tilesets by the first global tile id they cover
+#[derive(Default)]
+struct Gids {
+    first_gids: Vec<(u32, usize)>,
+}
+
+impl Gids {
+    fn add(&mut self, first_gid: u32, tileset: usize) {
+        self.first_gids.push((first_gid, tileset));
+        self.first_gids.sort_by_key(|(first_gid, _)| *first_gid);
+    }
+
+    /// `None` for gid 0, Tiled's empty cell
+    fn tile(&self, gid: u32) -> Result<Option<Tile>> {
+        let id = gid & GID_MASK;
+        if id == 0 {
+            return Ok(None);
+        }
+
+        let (first_gid, tileset) = self
+            .first_gids
+            .iter()
+            .rev()
+            .find(|(first_gid, _)| *first_gid <= id)
+            .ok_or_else(|| invalid(format!("tile {} has no tileset", id)))?;
+
+        Ok(Some(Tile {
+            tileset: *tileset,
+            id: id - first_gid,
+            flip_x: gid & FLIPPED_HORIZONTALLY != 0,
+            flip_y: gid & FLIPPED_VERTICALLY != 0,
+            flip_diagonal: gid & FLIPPED_DIAGONALLY != 0,
+        }))
+    }
+
+    fn tiles(&self, gids: &[u32], size: (u32, u32)) -> Result<Vec<Option<Tile>>> {
+        if gids.len() != (size.0 * size.1) as usize {
+            return Err(invalid(format!(
+                "{} tiles in a {}x{} layer",
+                gids.len(),
+                size.0,
+                size.1
+            )));
+        }
+
+        gids.iter().map(|gid| self.tile(*gid)).collect()
+    }
+}
+
+/// What a layer inherits from the groups it's in
+#[derive(Copy, Clone)]
+struct Group {
+    offset: Vec2,
+    opacity: f32,
+    visible: bool,
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Group {
+            offset: Vec2::zero(),
+            opacity: 1.0,
+            visible: true,
+        }
+    }
+}
+
+impl Group {
+    fn child(&self, offset: Vec2, opacity: f32, visible: bool) -> Group {
+        Group {
+            offset: self.offset + offset,
+            opacity: self.opacity * opacity,
+            visible: self.visible && visible,
+        }
+    }
+}
+
+/// Tile ids of a `data` element, either comma separated or base64 encoded
+fn decode_data(encoding: Option<&str>, compression: Option<&str>, data: &str) -> Result<Vec<u32>> {
+    let compression = compression.filter(|compression| !compression.is_empty());
+    if compression.is_some() && encoding != Some("base64") {
+        return Err(invalid("only base64 layers can be compressed"));
+    }
+
+    match encoding {
+        Some("csv") => data
+            .split(',')
+            .map(str::trim)
+            .filter(|gid| !gid.is_empty())
+            .map(|gid| {
+                gid.parse()
+                    .map_err(|_| invalid(format!("invalid tile {}", gid)))
+            })
+            .collect(),
+        Some("base64") => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(data.trim())
+                .map_err(|e| invalid(e.to_string()))?;
+            let bytes = decompress(compression, bytes)?;
+
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+                .collect())
+        }
+        Some(encoding) => Err(invalid(format!("unknown encoding {}", encoding))),
+        None => Err(invalid("missing layer encoding")),
+    }
+}
+
+fn decompress(compression: Option<&str>, bytes: Vec<u8>) -> Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    let read = match compression {
+        None => return Ok(bytes),
+        Some("zlib") => ZlibDecoder::new(bytes.as_slice()).read_to_end(&mut decompressed),
+        Some("gzip") => GzDecoder::new(bytes.as_slice()).read_to_end(&mut decompressed),
+        Some(compression) => {
+            return Err(invalid(format!(
+                "{} compressed layers are not supported, save the map with zlib, gzip or no compression",
+                compression
+            )))
+        }
+    };
+    read.map_err(|e| invalid(format!("corrupt compressed layer: {}", e)))?;
+
+    Ok(decompressed)
+}
+
+fn parse_property(kind: &str, value: &str) -> Result<Option<PropertyValue>> {
+    let error = || invalid(format!("invalid {} property {}", kind, value));
+
+    let value = match kind {
+        "bool" => PropertyValue::Bool(value.parse().map_err(|_| error())?),
+        "int" => PropertyValue::Int(value.parse().map_err(|_| error())?),
+        "float" => PropertyValue::Float(value.parse().map_err(|_| error())?),
+        "color" => PropertyValue::Color(parse_color(value).ok_or_else(error)?),
+        "file" => PropertyValue::File(String::from(value)),
+        "object" => PropertyValue::Object(value.parse().map_err(|_| error())?),
+        "string" => PropertyValue::String(String::from(value)),
+        _ => {
+            log::warn!("Skipping map property of unsupported type {}", kind);
+            return Ok(None);
+        }
+    };
+
+    Ok(Some(value))
+}
+
+/// Tiled colors are `#AARRGGBB` or `#RRGGBB`, an empty string when unset
+fn parse_color(value: &str) -> Option<Color> {
+    if value.is_empty() {
+        return Some(Color::default());
+    }
+
+    match value.len() {
+        7 => Color::from_hex(value).ok(),
+        9 => {
+            let alpha = u32::from_str_radix(value.get(1..3)?, 16).ok()?;
+            let mut color = Color::from_hex(format!("#{}", value.get(3..)?)).ok()?;
+            color.a = alpha as f32 / 255.0;
+
+            Some(color)
+        }
+        _ => None,
+    }
+}
+
+// TMX
+
+fn attribute<T: FromStr>(node: Node, name: &str) -> Result<Option<T>> {
+    node.attribute(name)
+        .map(|value| {
+            value.parse().map_err(|_| {
+                invalid(format!(
+                    "invalid {} {} on {}",
+                    name,
+                    value,
+                    node.tag_name().name()
+                ))
+            })
+        })
+        .transpose()
+}
+
+fn required<T: FromStr>(node: Node, name: &str) -> Result<T> {
+    attribute(node, name)?
+        .ok_or_else(|| invalid(format!("missing {} on {}", name, node.tag_name().name())))
+}
+
+fn tmx_properties(node: Node) -> Result<Properties> {
+    let mut properties = Properties::new();
+
+    let nodes = node
+        .children()
+        .filter(|child| child.has_tag_name("properties"))
+        .flat_map(|child| child.children())
+        .filter(|child| child.has_tag_name("property"));
+
+    for property in nodes {
+        let name: String = required(property, "name")?;
+        let kind = property.attribute("type").unwrap_or("string");
+        // Multiline strings are stored as the element text
+        let value = property
+            .attribute("value")
+            .or_else(|| property.text())
+            .unwrap_or("");
+
+        if let Some(value) = parse_property(kind, value)? {
+            properties.insert(name, value);
+        }
+    }
+
+    Ok(properties)
+}
+
+fn tmx_tileset(node: Node, dir: &Path) -> Result<Tileset> {
+    let grid = Grid {
+        cell_width: required(node, "tilewidth")?,
+        cell_height: required(node, "tileheight")?,
+        margin: attribute(node, "margin")?.unwrap_or(0),
+        spacing: attribute(node, "spacing")?.unwrap_or(0),
+    };
+    let name: String = attribute(node, "name")?.unwrap_or_default();
+
+    let mut tileset = Tileset::new(
+        &name,
+        grid,
+        attribute(node, "columns")?.unwrap_or(0),
+        attribute(node, "tilecount")?.unwrap_or(0),
+    );
+    tileset.image = node
+        .children()
+        .find(|child| child.has_tag_name("image"))
+        .and_then(|image| image.attribute("source"))
+        .map(|source| dir.join(source));
+
+    for tile in node.children().filter(|child| child.has_tag_name("tile")) {
+        let id = required(tile, "id")?;
+
+        let properties = tmx_properties(tile)?;
+        if !properties.is_empty() {
+            tileset.set_tile_properties(id, properties);
+        }
+
+        let frames = tile
+            .children()
+            .filter(|child| child.has_tag_name("animation"))
+            .flat_map(|child| child.children())
+            .filter(|child| child.has_tag_name("frame"))
+            .map(|frame| {
+                Ok(TileFrame {
+                    tile: required(frame, "tileid")?,
+                    duration: required::<u32>(frame, "duration")? as f32 / 1000.0,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        tileset.set_animation(id, frames);
+    }
+
+    warn_without_image(&tileset);
+
+    Ok(tileset)
+}
+
+fn tmx_layers(map: &mut Tilemap, gids: &Gids, parent: Node, group: &Group) -> Result<()> {
+    for node in parent.children().filter(Node::is_element) {
+        let inherited = group.child(
+            Vec2::new(
+                attribute(node, "offsetx")?.unwrap_or(0.0),
+                attribute(node, "offsety")?.unwrap_or(0.0),
+            ),
+            attribute(node, "opacity")?.unwrap_or(1.0),
+            attribute::<u32>(node, "visible")?.unwrap_or(1) != 0,
+        );
+        let name: String = attribute(node, "name")?.unwrap_or_default();
+
+        match node.tag_name().name() {
+            "layer" => {
+                let size = (required(node, "width")?, required(node, "height")?);
+                let data = node
+                    .children()
+                    .find(|child| child.has_tag_name("data"))
+                    .ok_or_else(|| invalid(format!("layer {} has no data", name)))?;
+
+                if data.children().any(|child| child.has_tag_name("chunk")) {
+                    return Err(invalid("infinite maps are not supported"));
+                }
+
+                let tile_gids = match data.attribute("encoding") {
+                    // Plain XML, one element per tile
+                    None => data
+                        .children()
+                        .filter(|child| child.has_tag_name("tile"))
+                        .map(|tile| Ok(attribute(tile, "gid")?.unwrap_or(0)))
+                        .collect::<Result<Vec<u32>>>()?,
+                    encoding => decode_data(
+                        encoding,
+                        data.attribute("compression"),
+                        data.text().unwrap_or(""),
+                    )?,
+                };
+
+                let mut layer =
+                    TileLayer::from_tiles(&name, size.0, size.1, gids.tiles(&tile_gids, size)?);
+                layer.visible = inherited.visible;
+                layer.opacity = inherited.opacity;
+                layer.offset = inherited.offset;
+                layer.properties = tmx_properties(node)?;
+
+                map.add_layer(Layer::Tiles(layer));
+            }
+            "objectgroup" => {
+                let mut layer = ObjectLayer::new(&name);
+                layer.visible = inherited.visible;
+                layer.offset = inherited.offset;
+                layer.properties = tmx_properties(node)?;
+                for object in node.children().filter(|child| child.has_tag_name("object")) {
+                    layer.objects.push(tmx_object(object, gids)?);
+                }
+
+                map.add_layer(Layer::Objects(layer));
+            }
+            "group" => tmx_layers(map, gids, node, &inherited)?,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn tmx_object(node: Node, gids: &Gids) -> Result<MapObject> {
+    let child = |name: &str| node.children().find(|child| child.has_tag_name(name));
+
+    let shape = if child("ellipse").is_some() {
+        Some(ObjectShape::Ellipse)
+    } else if child("point").is_some() {
+        Some(ObjectShape::Point)
+    } else if let Some(polygon) = child("polygon") {
+        Some(ObjectShape::Polygon(parse_points(polygon)?))
+    } else if let Some(polyline) = child("polyline") {
+        Some(ObjectShape::Polyline(parse_points(polyline)?))
+    } else {
+        None
+    };
+
+    new_object(
+        ObjectParts {
+            id: attribute(node, "id")?.unwrap_or(0),
+            name: attribute(node, "name")?.unwrap_or_default(),
+            class: attribute(node, "class")?
+                .or(attribute(node, "type")?)
+                .unwrap_or_default(),
+            position: Vec2::new(required(node, "x")?, required(node, "y")?),
+            size: Vec2::new(
+                attribute(node, "width")?.unwrap_or(0.0),
+                attribute(node, "height")?.unwrap_or(0.0),
+            ),
+            rotation: attribute(node, "rotation")?.unwrap_or(0.0),
+            visible: attribute::<u32>(node, "visible")?.unwrap_or(1) != 0,
+            gid: attribute(node, "gid")?,
+            shape,
+            properties: tmx_properties(node)?,
+        },
+        gids,
+    )
+}
+
+/// `points` attribute of polygons and polylines, "x,y x,y ..."
+fn parse_points(node: Node) -> Result<Vec<Vec2>> {
+    let points = node.attribute("points").unwrap_or("");
+
+    points
+        .split_whitespace()
+        .map(|point| {
+            let mut coordinates = point.split(',').map(f32::from_str);
+            match (coordinates.next(), coordinates.next()) {
+                (Some(Ok(x)), Some(Ok(y))) => Ok(Vec2::new(x, y)),
+                _ => Err(invalid(format!("invalid point {}", point))),
+            }
+        })
+        .collect()
+}
+
+// JSON, only the fields we use
+
+#[derive(Deserialize)]
+struct JsonMap {
+    width: u32,
+    height: u32,
+    tilewidth: u32,
+    tileheight: u32,
+    #[serde(default)]
+    orientation: String,
+    #[serde(default)]
+    infinite: bool,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+    #[serde(default)]
+    tilesets: Vec<JsonTileset>,
+    #[serde(default)]
+    layers: Vec<JsonLayer>,
+}
+
+#[derive(Deserialize)]
+struct JsonProperty {
+    name: String,
+    #[serde(rename = "type", default = "default_property_type")]
+    kind: String,
+    value: Value,
+}
+
+fn default_property_type() -> String {
+    String::from("string")
+}
+
+#[derive(Deserialize)]
+struct JsonTileset {
+    /// Only set in maps
+    firstgid: Option<u32>,
+    /// Set for external tilesets, nothing else is
+    source: Option<String>,
+    #[serde(default)]
+    name: String,
+    image: Option<String>,
+    #[serde(default)]
+    tilewidth: u32,
+    #[serde(default)]
+    tileheight: u32,
+    #[serde(default)]
+    margin: u32,
+    #[serde(default)]
+    spacing: u32,
+    #[serde(default)]
+    columns: u32,
+    #[serde(default)]
+    tilecount: u32,
+    #[serde(default)]
+    tiles: Vec<JsonTile>,
+}
+
+#[derive(Deserialize)]
+struct JsonTile {
+    id: u32,
+    #[serde(default)]
+    animation: Vec<JsonFrame>,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+}
+
+#[derive(Deserialize)]
+struct JsonFrame {
+    tileid: u32,
+    /// Milliseconds
+    duration: u32,
+}
+
+#[derive(Deserialize)]
+struct JsonLayer {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    width: u32,
+    #[serde(default)]
+    height: u32,
+    /// Array of gids, or a base64 string
+    data: Option<Value>,
+    encoding: Option<String>,
+    compression: Option<String>,
+    chunks: Option<Value>,
+    #[serde(default = "default_visible")]
+    visible: bool,
+    #[serde(default = "default_opacity")]
+    opacity: f32,
+    #[serde(default)]
+    offsetx: f32,
+    #[serde(default)]
+    offsety: f32,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+    #[serde(default)]
+    objects: Vec<JsonObject>,
+    /// Children of group layers
+    #[serde(default)]
+    layers: Vec<JsonLayer>,
+}
+
+fn default_visible() -> bool {
+    true
+}
+
+fn default_opacity() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize)]
+struct JsonObject {
+    #[serde(default)]
+    id: u32,
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    class: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    rotation: f32,
+    #[serde(default = "default_visible")]
+    visible: bool,
+    #[serde(default)]
+    ellipse: bool,
+    #[serde(default)]
+    point: bool,
+    polygon: Option<Vec<JsonPoint>>,
+    polyline: Option<Vec<JsonPoint>>,
+    gid: Option<u32>,
+    #[serde(default)]
+    properties: Vec<JsonProperty>,
+}
+
+#[derive(Deserialize)]
+struct JsonPoint {
+    x: f32,
+    y: f32,
+}
+
+fn json_properties(json: &[JsonProperty]) -> Result<Properties> {
+    let mut properties = Properties::new();
+
+    for property in json {
+        let value = match &property.value {
+            Value::String(value) => value.clone(),
+            value => value.to_string(),
+        };
+
+        if let Some(value) = parse_property(&property.kind, &value)? {
+            properties.insert(property.name.clone(), value);
+        }
+    }
+
+    Ok(properties)
+}
+
+fn json_tileset(json: &JsonTileset, dir: &Path) -> Result<Tileset> {
+    let grid = Grid {
+        cell_width: json.tilewidth,
+        cell_height: json.tileheight,
+        margin: json.margin,
+        spacing: json.spacing,
+    };
+
+    let mut tileset = Tileset::new(&json.name, grid, json.columns, json.tilecount);
+    tileset.image = json.image.as_ref().map(|image| dir.join(image));
+
+    for tile in &json.tiles {
+        let properties = json_properties(&tile.properties)?;
+        if !properties.is_empty() {
+            tileset.set_tile_properties(tile.id, properties);
+        }
+
+        tileset.set_animation(
+            tile.id,
+            tile.animation
+                .iter()
+                .map(|frame| TileFrame {
+                    tile: frame.tileid,
+                    duration: frame.duration as f32 / 1000.0,
+                })
+                .collect(),
+        );
+    }
+
+    warn_without_image(&tileset);
+
+    Ok(tileset)
+}
+
+fn json_layers(map: &mut Tilemap, gids: &Gids, layers: &[JsonLayer], group: &Group) -> Result<()> {
+    for json in layers {
+        let inherited = group.child(
+            Vec2::new(json.offsetx, json.offsety),
+            json.opacity,
+            json.visible,
+        );
+
+        match json.kind.as_str() {
+            "tilelayer" => {
+                if json.chunks.is_some() {
+                    return Err(invalid("infinite maps are not supported"));
+                }
+
+                let tile_gids = match &json.data {
+                    Some(Value::Array(data)) => data
+                        .iter()
+                        .map(|gid| {
+                            gid.as_u64()
+                                .map(|gid| gid as u32)
+                                .ok_or_else(|| invalid(format!("invalid tile {}", gid)))
+                        })
+                        .collect::<Result<Vec<u32>>>()?,
+                    Some(Value::String(data)) => {
+                        decode_data(json.encoding.as_deref(), json.compression.as_deref(), data)?
+                    }
+                    _ => return Err(invalid(format!("layer {} has no data", json.name))),
+                };
+
+                let size = (json.width, json.height);
+                let mut layer = TileLayer::from_tiles(
+                    &json.name,
+                    size.0,
+                    size.1,
+                    gids.tiles(&tile_gids, size)?,
+                );
+                layer.visible = inherited.visible;
+                layer.opacity = inherited.opacity;
+                layer.offset = inherited.offset;
+                layer.properties = json_properties(&json.properties)?;
+
+                map.add_layer(Layer::Tiles(layer));
+            }
+            "objectgroup" => {
+                let mut layer = ObjectLayer::new(&json.name);
+                layer.visible = inherited.visible;
+                layer.offset = inherited.offset;
+                layer.properties = json_properties(&json.properties)?;
+                for object in &json.objects {
+                    layer.objects.push(json_object(object, gids)?);
+                }
+
+                map.add_layer(Layer::Objects(layer));
+            }
+            "group" => json_layers(map, gids, &json.layers, &inherited)?,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn json_object(json: &JsonObject, gids: &Gids) -> Result<MapObject> {
+    let points = |points: &Vec<JsonPoint>| {
+        points
+            .iter()
+            .map(|point| Vec2::new(point.x, point.y))
+            .collect()
+    };
+
+    let shape = if json.ellipse {
+        Some(ObjectShape::Ellipse)
+    } else if json.point {
+        Some(ObjectShape::Point)
+    } else if let Some(polygon) = &json.polygon {
+        Some(ObjectShape::Polygon(points(polygon)))
+    } else {
+        json.polyline
+            .as_ref()
+            .map(|polyline| ObjectShape::Polyline(points(polyline)))
+    };
+
+    new_object(
+        ObjectParts {
+            id: json.id,
+            name: json.name.clone(),
+            class: if json.class.is_empty() {
+                json.kind.clone()
+            } else {
+                json.class.clone()
+            },
+            position: Vec2::new(json.x, json.y),
+            size: Vec2::new(json.width, json.height),
+            rotation: json.rotation,
+            visible: json.visible,
+            gid: json.gid,
+            shape,
+            properties: json_properties(&json.properties)?,
+        },
+        gids,
+    )
+}
+
+/// Object fields common to both formats
+struct ObjectParts {
+    id: u32,
+    name: String,
+    class: String,
+    position: Vec2,
+    size: Vec2,
+    /// Degrees
+    rotation: f32,
+    visible: bool,
+    gid: Option<u32>,
+    shape: Option<ObjectShape>,
+    properties: Properties,
+}
+
+fn new_object(parts: ObjectParts, gids: &Gids) -> Result<MapObject> {
+    let mut position = parts.position;
+
+    let shape = match parts.gid.map(|gid| gids.tile(gid)).transpose()?.flatten() {
+        Some(tile) => {
+            position.y -= parts.size.y;
+            ObjectShape::Tile(tile)
+        }
+        None => parts.shape.unwrap_or(ObjectShape::Rectangle),
+    };
+
+    Ok(MapObject {
+        id: parts.id,
+        name: parts.name,
+        class: parts.class,
+        position,
+        width: parts.size.x,
+        height: parts.size.y,
+        rotation: parts.rotation.to_radians(),
+        visible: parts.visible,
+        shape,
+        properties: parts.properties,
+    })
+}
+
+fn warn_without_image(tileset: &Tileset) {
+    if tileset.image.is_none() {
+        log::warn!(
+            "Tileset {} has no single image, image collections are not supported",
+            tileset.name
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn fixtures() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tiled")
+    }
+
+    fn read(name: &str) -> String {
+        fs::read_to_string(fixtures().join(name)).unwrap()
+    }
+
+    fn is_invalid(result: Result<Tilemap>) -> bool {
+        matches!(result, Err(JellyError::InvalidTilemap(_)))
+    }
+
+    /// Both fixtures describe the same map
+    fn check_map(map: &Tilemap) {
+        assert_eq!((map.width, map.height), (4, 2));
+        assert_eq!((map.tile_width, map.tile_height), (16, 16));
+        assert_eq!(
+            map.properties.get("music"),
+            Some(&PropertyValue::String(String::from("forest.ogg")))
+        );
+
+        let names: Vec<&str> = map.layers.iter().map(Layer::name).collect();
+        assert_eq!(names, ["ground", "decor", "things"]);
+
+        let ground = map.tile_layer("ground").unwrap();
+        assert_eq!(ground.offset, Vec2::zero());
+        assert_eq!(ground.get(0, 0), Some(Tile::new(0, 0)));
+        assert_eq!(ground.get(1, 0), Some(Tile::new(0, 1)));
+        assert_eq!(ground.get(2, 0), None);
+        assert_eq!(ground.get(3, 1), None);
+    }
+
+    fn check_tilesets(map: &Tilemap) {
+        let tiles = &map.tilesets[0];
+        assert_eq!(tiles.name, "tiles");
+        assert_eq!(tiles.image, Some(fixtures().join("tiles.png")));
+        assert_eq!(
+            tiles.tile_properties(0).unwrap().get("solid"),
+            Some(&PropertyValue::Bool(true))
+        );
+        let frames = tiles.animation(0).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!((frames[1].tile, frames[1].duration), (1, 0.25));
+
+        // External, images relative to the tileset file
+        let terrain = &map.tilesets[1];
+        assert_eq!(terrain.name, "terrain");
+        assert_eq!(terrain.image, Some(fixtures().join("terrain.png")));
+        assert_eq!((terrain.grid.margin, terrain.grid.spacing), (2, 1));
+        assert_eq!(
+            terrain.tile_properties(2).unwrap().get("kind"),
+            Some(&PropertyValue::String(String::from("water")))
+        );
+    }
+
+    fn check_flips_and_first_gids(map: &Tilemap) {
+        let ground = map.tile_layer("ground").unwrap();
+        // Gid 5 is the first tile of the external tileset
+        assert_eq!(ground.get(3, 0), Some(Tile::new(1, 0)));
+        assert_eq!(ground.get(0, 1), Some(Tile::new(0, 0).flipped(true, false)));
+        assert_eq!(ground.get(1, 1), Some(Tile::new(1, 1).flipped(false, true)));
+        assert_eq!(
+            ground.get(2, 1),
+            Some(Tile {
+                flip_diagonal: true,
+                ..Tile::new(0, 0)
+            })
+        );
+    }
+
+    fn check_base64_and_groups(map: &Tilemap) {
+        let decor = map.tile_layer("decor").unwrap();
+        assert_eq!(decor.tiles().count(), 3);
+        assert_eq!(decor.get(1, 0), Some(Tile::new(0, 2)));
+        assert_eq!(decor.get(0, 1), Some(Tile::new(0, 3)));
+        assert_eq!(decor.get(3, 1), Some(Tile::new(1, 2).flipped(true, false)));
+
+        // Offsets add up, opacity multiplies and visibility is inherited
+        assert_eq!(decor.offset, Vec2::new(12.0, 5.0));
+        assert_eq!(decor.opacity, 0.5);
+        assert!(!decor.visible);
+
+        let things = map.object_layer("things").unwrap();
+        assert_eq!(things.offset, Vec2::new(10.0, 5.0));
+        assert!(things.visible);
+    }
+
+    fn check_objects(map: &Tilemap) {
+        let things = map.object_layer("things").unwrap();
+        assert_eq!(things.objects.len(), 6);
+
+        let spawn = things.object("spawn").unwrap();
+        assert_eq!(spawn.class, "Spawn");
+        assert_eq!(spawn.position, Vec2::new(8.0, 24.0));
+        assert_eq!((spawn.width, spawn.height), (16.0, 8.0));
+        assert!((spawn.rotation - FRAC_PI_2).abs() < 1e-6);
+        assert_eq!(spawn.shape, ObjectShape::Rectangle);
+        assert_eq!(spawn.property("health"), Some(&PropertyValue::Int(3)));
+        assert_eq!(spawn.property("speed"), Some(&PropertyValue::Float(1.5)));
+        assert_eq!(spawn.property("target"), Some(&PropertyValue::Object(2)));
+        assert_eq!(
+            spawn.property("label").and_then(PropertyValue::as_str),
+            Some("start")
+        );
+        let tint = spawn
+            .property("tint")
+            .and_then(PropertyValue::as_color)
+            .unwrap();
+        assert_eq!((tint.r, tint.g, tint.b), (1.0, 0.0, 0.0));
+        assert!((tint.a - 128.0 / 255.0).abs() < 1e-6);
+
+        assert_eq!(things.object("pond").unwrap().shape, ObjectShape::Ellipse);
+        assert_eq!(things.object("marker").unwrap().shape, ObjectShape::Point);
+        assert_eq!(
+            things.object("zone").unwrap().shape,
+            ObjectShape::Polygon(vec![
+                Vec2::new(0.0, 0.0),
+                Vec2::new(16.0, 0.0),
+                Vec2::new(8.0, 12.0)
+            ])
+        );
+        let path = things.object("path").unwrap();
+        assert!(!path.visible);
+        assert_eq!(
+            path.shape,
+            ObjectShape::Polyline(vec![
+                Vec2::new(0.0, 0.0),
+                Vec2::new(4.0, 4.0),
+                Vec2::new(8.0, 0.0)
+            ])
+        );
+
+        // Tile objects are placed by their bottom left corner in Tiled
+        let crate_object = things.object("crate").unwrap();
+        assert_eq!(crate_object.position, Vec2::new(16.0, 16.0));
+        assert_eq!(
+            crate_object.shape,
+            ObjectShape::Tile(Tile::new(1, 1).flipped(false, true))
+        );
+    }
+
+    fn check_all(map: &Tilemap) {
+        check_map(map);
+        check_tilesets(map);
+        check_flips_and_first_gids(map);
+        check_base64_and_groups(map);
+        check_objects(map);
+    }
+
+    #[test]
+    fn reads_tmx() {
+        let map = Tilemap::from_tmx(&read("map.tmx"), &fixtures()).unwrap();
+        check_all(&map);
+    }
+
+    #[test]
+    fn reads_json() {
+        let map = Tilemap::from_tiled_json(&read("map.json"), &fixtures()).unwrap();
+        check_all(&map);
+    }
+
+    #[test]
+    fn loads_by_extension() {
+        let map = load(&fixtures().join("map.tmx")).unwrap();
+        assert_eq!(map.tilesets.len(), 2);
+        assert!(matches!(
+            load(&fixtures().join("missing.tmx")),
+            Err(JellyError::AssetNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn reads_compressed_data() {
+        // Gids 1, 0, 2 flipped horizontally and 3
+        let zlib = "eJxjZIAAJgaGBmYgDQACvACH";
+        let gzip = "H4sIAAAAAAACA2NkgAAmBoYGZiANAMXCpaMQAAAA";
+        let check = |map: Tilemap| {
+            let layer = map.tile_layer("ground").unwrap();
+            assert_eq!(layer.get(0, 0), Some(Tile::new(0, 0)));
+            assert_eq!(layer.get(1, 0), None);
+            assert_eq!(layer.get(0, 1), Some(Tile::new(0, 1).flipped(true, false)));
+            assert_eq!(layer.get(1, 1), Some(Tile::new(0, 2)));
+        };
+
+        let tmx = format!(
+            r#"<map orientation="orthogonal" width="2" height="2" tilewidth="8" tileheight="8">
+                <tileset firstgid="1" name="tiles" tilewidth="8" tileheight="8" tilecount="4" columns="2">
+                    <image source="tiles.png" width="16" height="16"/>
+                </tileset>
+                <layer name="ground" width="2" height="2">
+                    <data encoding="base64" compression="zlib">{}</data>
+                </layer>
+            </map>"#,
+            zlib
+        );
+        check(Tilemap::from_tmx(&tmx, Path::new("")).unwrap());
+
+        let json = format!(
+            r#"{{"width": 2, "height": 2, "tilewidth": 8, "tileheight": 8,
+                "orientation": "orthogonal",
+                "tilesets": [{{"firstgid": 1, "name": "tiles", "tilewidth": 8, "tileheight": 8,
+                    "tilecount": 4, "columns": 2, "image": "tiles.png",
+                    "imagewidth": 16, "imageheight": 16}}],
+                "layers": [{{"type": "tilelayer", "name": "ground", "width": 2, "height": 2,
+                    "encoding": "base64", "compression": "gzip", "data": "{}"}}]}}"#,
+            gzip
+        );
+        check(Tilemap::from_tiled_json(&json, Path::new("")).unwrap());
+    }
+
+    #[test]
+    fn rejects_unsupported_compression() {
+        let tmx = r#"<map orientation="orthogonal" width="1" height="1" tilewidth="8" tileheight="8">
+            <layer name="ground" width="1" height="1">
+                <data encoding="base64" compression="zstd">KLUv/SAEIQAAAQAAAA==</data>
+            </layer>
+        </map>"#;
+        assert!(is_invalid(Tilemap::from_tmx(tmx, Path::new(""))));
+
+        // Not zlib data
+        let json = r#"{"width": 1, "height": 1, "tilewidth": 8, "tileheight": 8,
+            "orientation": "orthogonal", "layers": [{"type": "tilelayer", "name": "ground",
+            "width": 1, "height": 1, "encoding": "base64", "compression": "zlib",
+            "data": "AQAAAA=="}]}"#;
+        assert!(is_invalid(Tilemap::from_tiled_json(json, Path::new(""))));
+    }
+
+    #[test]
+    fn rejects_infinite_maps() {
+        let tmx = r#"<map orientation="orthogonal" infinite="1" width="1" height="1"
+            tilewidth="8" tileheight="8"/>"#;
+        assert!(is_invalid(Tilemap::from_tmx(tmx, Path::new(""))));
+
+        let json = r#"{"width": 1, "height": 1, "tilewidth": 8, "tileheight": 8,
+            "orientation": "orthogonal", "infinite": true}"#;
+        assert!(is_invalid(Tilemap::from_tiled_json(json, Path::new(""))));
+
+        // Chunked data in a map not flagged as infinite
+        let tmx = r#"<map orientation="orthogonal" width="1" height="1" tilewidth="8" tileheight="8">
+            <layer name="ground" width="1" height="1">
+                <data encoding="csv"><chunk x="0" y="0" width="1" height="1">1</chunk></data>
+            </layer>
+        </map>"#;
+        assert!(is_invalid(Tilemap::from_tmx(tmx, Path::new(""))));
+    }
+
+    #[test]
+    fn rejects_other_orientations() {
+        let tmx =
+            r#"<map orientation="isometric" width="1" height="1" tilewidth="8" tileheight="8"/>"#;
+        assert!(is_invalid(Tilemap::from_tmx(tmx, Path::new(""))));
+    }
+}
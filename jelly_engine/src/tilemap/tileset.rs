@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::prelude::Properties;
+use crate::graphics::prelude::{Grid, Texture, TextureRegion};
+
+/// Frame of an animated tile
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TileFrame {
+    /// Tile shown, in the same tileset
+    pub tile: u32,
+    /// Seconds
+    pub duration: f32,
+}
+
+/// Tiles cut from a single image on a regular grid, numbered row by row from 0
+#[derive(Debug, Clone)]
+pub struct Tileset {
+    pub name: String,
+    /// Image the texture is loaded from, see `Tilemap::load_textures`
+    pub image: Option<PathBuf>,
+    /// Nothing is drawn with the tileset until it's set
+    pub texture: Option<Texture>,
+    pub grid: Grid,
+    pub columns: u32,
+    pub tile_count: u32,
+    animations: HashMap<u32, Vec<TileFrame>>,
+    properties: HashMap<u32, Properties>,
+}
+
+impl Tileset {
+    pub fn new(name: &str, grid: Grid, columns: u32, tile_count: u32) -> Tileset {
+        Tileset {
+            name: String::from(name),
+            image: None,
+            texture: None,
+            grid,
+            columns,
+            tile_count,
+            animations: HashMap::new(),
+            properties: HashMap::new(),
+        }
+    }
+
+    /// Tileset using every cell of `texture`
+    pub fn from_texture(name: &str, texture: Texture, grid: Grid) -> Tileset {
+        let cells = |size: u32, cell: u32| {
+            let usable = size.saturating_sub(grid.margin * 2) + grid.spacing;
+            usable / (cell + grid.spacing).max(1)
+        };
+        let columns = cells(texture.width(), grid.cell_width);
+        let rows = cells(texture.height(), grid.cell_height);
+
+        Tileset {
+            texture: Some(texture),
+            ..Tileset::new(name, grid, columns, columns * rows)
+        }
+    }
+
+    pub fn tile_width(&self) -> u32 {
+        self.grid.cell_width
+    }
+
+    pub fn tile_height(&self) -> u32 {
+        self.grid.cell_height
+    }
+
+    /// Top left corner of `tile` in the image, in pixels
+    pub fn tile_position(&self, tile: u32) -> (u32, u32) {
+        let columns = self.columns.max(1);
+        let (column, row) = (tile % columns, tile / columns);
+
+        (
+            self.grid.margin + column * (self.grid.cell_width + self.grid.spacing),
+            self.grid.margin + row * (self.grid.cell_height + self.grid.spacing),
+        )
+    }
+
+    /// `None` without texture or past the last tile
+    pub fn region(&self, tile: u32) -> Option<TextureRegion> {
+        if tile >= self.tile_count {
+            return None;
+        }
+
+        let (x, y) = self.tile_position(tile);
+        self.texture.map(|texture| {
+            TextureRegion::new(texture, x, y, self.grid.cell_width, self.grid.cell_height)
+        })
+    }
+
+    /// Animate `tile` through `frames`
+    pub fn set_animation(&mut self, tile: u32, frames: Vec<TileFrame>) {
+        if frames.is_empty() {
+            self.animations.remove(&tile);
+        } else {
+            self.animations.insert(tile, frames);
+        }
+    }
+
+    pub fn animation(&self, tile: u32) -> Option<&[TileFrame]> {
+        self.animations.get(&tile).map(Vec::as_slice)
+    }
+
+    pub fn is_animated(&self, tile: u32) -> bool {
+        self.animations.contains_key(&tile)
+    }
+
+    /// Tile shown in place of `tile` after `time` seconds, `tile` itself when it isn't animated
+    pub fn animated_tile(&self, tile: u32, time: f32) -> u32 {
+        let frames = match self.animations.get(&tile) {
+            Some(frames) => frames,
+            None => return tile,
+        };
+
+        let duration: f32 = frames.iter().map(|frame| frame.duration).sum();
+        if duration <= 0.0 {
+            return frames[0].tile;
+        }
+
+        let mut time = time.rem_euclid(duration);
+        for frame in frames {
+            if time < frame.duration {
+                return frame.tile;
+            }
+            time -= frame.duration;
+        }
+
+        frames[frames.len() - 1].tile
+    }
+
+    pub fn set_tile_properties(&mut self, tile: u32, properties: Properties) {
+        self.properties.insert(tile, properties);
+    }
+
+    pub fn tile_properties(&self, tile: u32) -> Option<&Properties> {
+        self.properties.get(&tile)
+    }
+}
@@ -0,0 +1,9 @@
+<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" name="terrain" tilewidth="16" tileheight="16" spacing="1" margin="2" tilecount="4" columns="2">
+ <image source="terrain.png" width="36" height="36"/>
+ <tile id="2">
+  <properties>
+   <property name="kind" value="water"/>
+  </properties>
+ </tile>
+</tileset>